@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hiero_pack::*;
+
+/// lays out the same text through the `HashMap` tables and through a `GlyphLookup`
+fn glyph_lookup(c: &mut Criterion) {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let mut atlas = HieroAtlas::new().with_font_file(font_file).build().unwrap();
+    // a few thousand cjk glyphs so lookups outside latin-1 hit a realistic table size
+    for id in 0x4E00..0x5E00 {
+        atlas
+            .bitmap_table
+            .insert(GlyphId(id), HieroBitmapInfo::default());
+    }
+    atlas.kerning_table.insert(('A'.into(), 'V'.into()), -2);
+    atlas.kerning_table.insert(('T'.into(), 'o'.into()), -1);
+    let lookup = atlas.lookup();
+
+    let texts = [
+        (
+            "latin",
+            "Sphinx of black quartz, judge my vow. AVAST To the docks! ".repeat(16),
+        ),
+        (
+            "cjk",
+            (0x4E00..0x4F00).filter_map(std::char::from_u32).collect(),
+        ),
+    ];
+    let mut group = c.benchmark_group("glyph_lookup");
+    for (name, text) in texts.iter() {
+        group.bench_function(format!("hash_map/{}", name), |b| {
+            b.iter(|| {
+                let (mut pen, mut previous) = (0, None);
+                for c in text.chars() {
+                    let id = GlyphId::from(c);
+                    if let Some(glyph) = atlas.bitmap_table.get(&id) {
+                        if let Some(previous) = previous {
+                            pen += atlas
+                                .kerning_table
+                                .get(&(previous, id))
+                                .copied()
+                                .unwrap_or(0);
+                        }
+                        pen += glyph.xadvance;
+                        previous = Some(id);
+                    }
+                }
+                pen
+            })
+        });
+        group.bench_function(format!("lookup/{}", name), |b| {
+            b.iter(|| {
+                let (mut pen, mut previous) = (0, None);
+                for c in text.chars() {
+                    if let Some(glyph) = lookup.glyph(c) {
+                        if let Some(previous) = previous {
+                            pen += lookup.kerning(previous, c);
+                        }
+                        pen += glyph.xadvance;
+                        previous = Some(c);
+                    }
+                }
+                pen
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, glyph_lookup);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hiero_pack::*;
+
+/// compares how long each page encoding takes to decode, sizes are printed alongside
+fn page_decode(c: &mut Criterion) {
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let page = HieroPage::decode_png(&page_file).unwrap();
+
+    let mut group = c.benchmark_group("page_decode");
+    for &encoding in PageEncoding::ALL.iter().filter(|e| e.is_supported()) {
+        let compressed = CompressedPage::encode(&page, encoding).unwrap();
+        println!("{}: {} bytes", encoding, compressed.data.len());
+        group.bench_function(encoding.name(), |b| b.iter(|| compressed.decode().unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, page_decode);
+criterion_main!(benches);
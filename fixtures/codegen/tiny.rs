@@ -0,0 +1,131 @@
+// generated by hiero_pack from "Tiny \"Test\"", do not edit
+
+/// mirrors `hiero_pack::GlyphId`, `char`s and `u32`s convert into it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GlyphId(pub u32);
+
+impl From<char> for GlyphId {
+    fn from(c: char) -> Self {
+        GlyphId(c as u32)
+    }
+}
+
+impl From<u32> for GlyphId {
+    fn from(id: u32) -> Self {
+        GlyphId(id)
+    }
+}
+
+/// mirrors `hiero_pack::HieroBitmapInfo`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub xoffset: i32,
+    pub yoffset: i32,
+    pub xadvance: i32,
+    pub page: i32,
+    pub channel: i32,
+}
+
+/// mirrors `hiero_pack::ColorSpace`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+/// mirrors `hiero_pack::AlphaMode`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    Straight,
+    Premultiplied,
+}
+
+/// a page as a png file along with the conventions its texels are stored in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    pub png: &'static [u8],
+    pub color_space: ColorSpace,
+    pub alpha_mode: AlphaMode,
+}
+
+pub mod info {
+    pub const FACE: &str = "Tiny \"Test\"";
+    pub const SIZE: i32 = 0;
+    pub const BOLD: i32 = 0;
+    pub const ITALIC: i32 = 0;
+    pub const CHARSET: &str = "";
+    pub const UNICODE: i32 = 0;
+    pub const STRETCH_H: i32 = 0;
+    pub const SMOOTH: i32 = 0;
+    pub const AA: i32 = 0;
+    pub const PADDING: &[i32] = &[0, 0, 0, 0];
+    pub const SPACING: &[i32] = &[1, 1];
+    pub const OUTLINE: Option<i32> = None;
+}
+
+pub mod common {
+    pub const LINE_HEIGHT: i32 = 4;
+    pub const BASE: i32 = 3;
+    pub const SCALE_W: i32 = 8;
+    pub const SCALE_H: i32 = 4;
+    pub const PAGES: i32 = 1;
+    pub const PACKED: i32 = 0;
+    pub const ALPHA_CHNL: Option<i32> = Some(0);
+    pub const RED_CHNL: Option<i32> = None;
+    pub const GREEN_CHNL: Option<i32> = None;
+    pub const BLUE_CHNL: Option<i32> = None;
+}
+
+/// sorted by `id`
+pub const GLYPHS: &[Glyph] = &[
+    Glyph { id: 0x0027, x: 6, y: 0, width: 2, height: 3, xoffset: 0, yoffset: 0, xadvance: 1, page: 0, channel: 15 },
+    Glyph { id: 0x0061, x: 0, y: 0, width: 2, height: 3, xoffset: 0, yoffset: 0, xadvance: 3, page: 0, channel: 15 },
+    Glyph { id: 0x0062, x: 3, y: 0, width: 2, height: 3, xoffset: 0, yoffset: 0, xadvance: 3, page: 0, channel: 15 },
+    Glyph { id: 0xd800, x: 0, y: 0, width: 2, height: 3, xoffset: 0, yoffset: 0, xadvance: 1, page: 0, channel: 15 },
+];
+
+/// `(first, second, amount)` sorted by pair
+pub const KERNING: &[(u32, u32, i32)] = &[
+    (0x0027, 0x0061, 1),
+    (0x0061, 0x0062, -1),
+];
+
+/// `(name, id)` sorted by name
+pub const NAMES: &[(&str, u32)] = &[
+    ("icon", 0xd800),
+    ("quote", 0x0027),
+];
+
+pub const PAGES: &[Page] = &[
+    Page { png: include_bytes!("tiny_page0.png"), color_space: ColorSpace::Srgb, alpha_mode: AlphaMode::Premultiplied },
+];
+
+/// the glyph of a character or `GlyphId`
+pub fn glyph<G: Into<GlyphId>>(glyph: G) -> Option<&'static Glyph> {
+    let id = glyph.into().0;
+    GLYPHS
+        .binary_search_by_key(&id, |glyph| glyph.id)
+        .ok()
+        .map(|index| &GLYPHS[index])
+}
+
+/// the kerning amount between two glyphs, 0 if the pair isn't kerned
+pub fn kerning<G: Into<GlyphId>>(first: G, second: G) -> i32 {
+    let pair = (first.into().0, second.into().0);
+    KERNING
+        .binary_search_by_key(&pair, |&(a, b, _)| (a, b))
+        .map_or(0, |index| KERNING[index].2)
+}
+
+/// the glyph named `name`
+pub fn glyph_by_name(name: &str) -> Option<&'static Glyph> {
+    NAMES
+        .binary_search_by_key(&name, |&(name, _)| name)
+        .ok()
+        .and_then(|index| glyph(NAMES[index].1))
+}
@@ -1,50 +1,115 @@
 use super::*;
 
+mod bcode;
 mod builder;
+mod charset;
+mod code_page;
+mod codegen;
+mod color;
 mod common;
+mod compressed;
+mod diff;
+mod draw;
+mod glyph;
 mod info;
+mod lookup;
+mod metadata;
+mod mipmap;
+mod optimize;
+mod overlay;
+mod packer;
 mod page;
+mod preview;
+mod subset;
+mod texture;
+mod validate;
 
-pub use self::{builder::*, common::*, info::*, page::*};
+pub use self::{
+    bcode::*, builder::*, charset::*, code_page::*, color::*, common::*, compressed::*, diff::*,
+    glyph::*, info::*, lookup::*, metadata::*, mipmap::*, optimize::*, overlay::*, page::*,
+    preview::*, texture::*, validate::*,
+};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct HieroAtlas {
     pub info: HieroInfo,
     pub common: HieroCommon,
-    pub bitmap_table: HashMap<char, HieroBitmapInfo>,
-    pub kerning_table: HashMap<(char, char), i32>,
-    pub compressed_pages: Vec<Vec<u8>>,
+    pub bitmap_table: HashMap<GlyphId, HieroBitmapInfo>,
+    pub kerning_table: HashMap<(GlyphId, GlyphId), i32>,
+    /// symbolic names of glyphs, see [`HieroAtlas::glyph_by_name`]
+    pub glyph_names: GlyphNames,
+    pub compressed_pages: Vec<CompressedPage>,
+    /// glyph aware mip levels of every page (base level excluded), see [`HieroAtlas::generate_mips`]
+    pub mip_pages: Vec<Vec<CompressedPage>>,
+    /// gpu textures exported from the pages, see [`HieroAtlas::export_textures`]
+    pub textures: Vec<TextureRef>,
 }
 
-impl HieroAtlas {
-    pub fn new() -> Incomplete<Self> {
-        Incomplete::new(Self {
+/// an atlas without glyphs or pages
+impl Default for HieroAtlas {
+    fn default() -> Self {
+        Self {
             info: HieroInfo::default(),
             common: HieroCommon::default(),
             bitmap_table: HashMap::new(),
             kerning_table: HashMap::new(),
+            glyph_names: GlyphNames::new(),
             compressed_pages: Vec::new(),
-        })
+            mip_pages: Vec::new(),
+            textures: Vec::new(),
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// starts building an atlas, a font source has to be given before it can be built
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> AtlasBuilder<NoFont> {
+        AtlasBuilder::new()
+    }
+
+    /// the glyph of a character or [`GlyphId`]
+    pub fn glyph<G: Into<GlyphId>>(&self, glyph: G) -> Option<&HieroBitmapInfo> {
+        self.bitmap_table.get(&glyph.into())
     }
 
-    pub fn deserialize(data: Vec<u8>) -> Result<Self, Error> {
-        Ok(bincode::deserialize::<Self>(&data[..])?)
+    /// the kerning amount between two glyphs, 0 if the pair isn't kerned
+    pub fn kerning<G: Into<GlyphId>>(&self, first: G, second: G) -> i32 {
+        self.kerning_table
+            .get(&(first.into(), second.into()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// looks up a glyph through [`HieroAtlas::glyph_names`]
+    pub fn glyph_by_name(&self, name: &str) -> Option<&HieroBitmapInfo> {
+        self.glyph_names.get(name).and_then(|id| self.glyph(id))
+    }
+
+    /// the glyphs stored under a character, glyphs whose id isn't one are skipped
+    pub fn char_glyphs(&self) -> impl Iterator<Item = (char, &HieroBitmapInfo)> + '_ {
+        self.bitmap_table
+            .iter()
+            .filter_map(|(id, bitmap)| Some((id.as_char()?, bitmap)))
     }
 
     /// decodes a compressed page and returns a heap allocated image
-    pub fn try_unpack_page(&self, page_index: usize) -> Result<HieroPage, PageUnpackError> {
-        let opt = self.compressed_pages.get(page_index).map(|page| {
-            let decoder = png::Decoder::new(&page[..]);
-            decoder.read_info().map(|(png_info, mut reader)| {
-                let mut page_buffer = vec![0; png_info.buffer_size()];
-                reader.next_frame(&mut page_buffer).unwrap();
-                HieroPage::from(png_info).with_pixels(page_buffer)
-            })
-        });
-        match opt {
-            Some(Ok(page)) => Ok(page),
-            Some(Err(decode_err)) => Err(PageUnpackError::PageDecodeError(decode_err)),
-            None => Err(PageUnpackError::InvalidIndex),
-        }
+    pub fn try_unpack_page(&self, page_index: usize) -> Result<HieroPage, Error> {
+        self.compressed_pages
+            .get(page_index)
+            .ok_or(Error::PageUnpack(PageUnpackError::InvalidIndex))?
+            .decode()
+    }
+
+    /// decodes a compressed page, `transform` decides whether 16-bit and indexed png pages are normalized
+    pub fn try_unpack_page_transformed(
+        &self,
+        page_index: usize,
+        transform: PngTransform,
+    ) -> Result<HieroPage, Error> {
+        self.compressed_pages
+            .get(page_index)
+            .ok_or(Error::PageUnpack(PageUnpackError::InvalidIndex))?
+            .decode_with(transform)
     }
 }
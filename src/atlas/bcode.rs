@@ -0,0 +1,166 @@
+use super::*;
+
+/// first bytes of every .bcode file written since the format got versioned
+const MAGIC: &[u8; 4] = b"HPAK";
+/// bumped whenever the serialized layout of [`HieroAtlas`] changes
+pub const BCODE_VERSION: u32 = 1;
+
+/// The layout 0.1.3 and earlier wrote without a header: png pages and `char` keyed tables
+#[derive(Deserialize)]
+struct LegacyAtlas {
+    info: LegacyInfo,
+    common: LegacyCommon,
+    bitmap_table: HashMap<char, HieroBitmapInfo>,
+    kerning_table: HashMap<(char, char), i32>,
+    compressed_pages: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct LegacyInfo {
+    face: String,
+    size: i32,
+    bold: i32,
+    italic: i32,
+    char_set: String,
+    unicode: i32,
+    stretch_h: i32,
+    smooth: i32,
+    aa: i32,
+    padding: Vec<i32>,
+    spacing: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+struct LegacyCommon {
+    line_height: i32,
+    base: i32,
+    scale_w: i32,
+    scale_h: i32,
+    pages: i32,
+    packed: i32,
+}
+
+impl From<LegacyAtlas> for HieroAtlas {
+    fn from(legacy: LegacyAtlas) -> Self {
+        let LegacyInfo {
+            face,
+            size,
+            bold,
+            italic,
+            char_set,
+            unicode,
+            stretch_h,
+            smooth,
+            aa,
+            padding,
+            spacing,
+        } = legacy.info;
+        let LegacyCommon {
+            line_height,
+            base,
+            scale_w,
+            scale_h,
+            pages,
+            packed,
+        } = legacy.common;
+        Self {
+            info: HieroInfo {
+                face,
+                size,
+                bold,
+                italic,
+                char_set,
+                unicode,
+                stretch_h,
+                smooth,
+                aa,
+                padding,
+                spacing,
+                outline: None,
+            },
+            common: HieroCommon {
+                line_height,
+                base,
+                scale_w,
+                scale_h,
+                pages,
+                packed,
+                ..HieroCommon::default()
+            },
+            bitmap_table: legacy
+                .bitmap_table
+                .into_iter()
+                .map(|(key, glyph)| (key.into(), glyph))
+                .collect(),
+            kerning_table: legacy
+                .kerning_table
+                .into_iter()
+                .map(|((first, second), amount)| ((first.into(), second.into()), amount))
+                .collect(),
+            compressed_pages: legacy
+                .compressed_pages
+                .into_iter()
+                .map(CompressedPage::png)
+                .collect(),
+            ..HieroAtlas::default()
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// the atlas as a .bcode file: a `HPAK` magic and [`BCODE_VERSION`] followed by the bincode of the atlas
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&BCODE_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self)?;
+        Ok(bytes)
+    }
+
+    /// Reads a .bcode file written by [`HieroAtlas::to_bytes`].\
+    /// Files without the header are taken to be in the layout of 0.1.3 and earlier and are upgraded,
+    /// files written by a newer version of the format are rejected.
+    pub fn deserialize(data: Vec<u8>) -> Result<Self, Error> {
+        match data.strip_prefix(&MAGIC[..]) {
+            Some(rest) if rest.len() >= 4 => {
+                let version = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+                if version != BCODE_VERSION {
+                    return Err(Error::UnsupportedBcodeVersion(version));
+                }
+                Ok(bincode::deserialize(&rest[4..])?)
+            }
+            _ => Ok(bincode::deserialize::<LegacyAtlas>(&data)?.into()),
+        }
+    }
+}
+
+#[test]
+fn bcode_versions() {
+    // written by 0.1.3, before pages had an encoding and glyphs a GlyphId
+    let legacy = std::fs::read("./fixtures/legacy/uroob.bcode").unwrap();
+    let atlas = HieroAtlas::deserialize(legacy).unwrap();
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let expected = HieroAtlas::new()
+        .with_font_str(&font_file)
+        .with_pages(vec![page_file.clone()])
+        .build()
+        .unwrap();
+    assert_eq!(atlas.info, expected.info);
+    assert_eq!(atlas.common, expected.common);
+    assert_eq!(atlas.bitmap_table, expected.bitmap_table);
+    assert_eq!(atlas.kerning_table, expected.kerning_table);
+    assert_eq!(atlas.compressed_pages[0].data, page_file);
+    assert_eq!(atlas.kerning('A', 'V'), expected.kerning('A', 'V'));
+
+    let bytes = expected.to_bytes().unwrap();
+    assert!(bytes.starts_with(b"HPAK"));
+    let reloaded = HieroAtlas::deserialize(bytes.clone()).unwrap();
+    assert_eq!(reloaded.bitmap_table, expected.bitmap_table);
+
+    let mut future = bytes;
+    future[4..8].copy_from_slice(&(BCODE_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        HieroAtlas::deserialize(future),
+        Err(Error::UnsupportedBcodeVersion(2))
+    ));
+}
@@ -1,33 +1,238 @@
-use super::*; 
+use super::*;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 
-pub trait AtlasBuilder: Sized {
-    type AtlasType;
-    fn with_font_file(self, font: String) -> Result<Self, &'static str>;
-    fn with_pages(self, pages: Vec<Vec<u8>>) -> Self;
-    fn build(self) -> Self::AtlasType;
+/// Builder state before a font source was given, [`AtlasBuilder::build`] isn't available yet
+pub struct NoFont;
+
+/// Builder state once a font source was given
+pub struct WithFont<'a>(FontSource<'a>);
+
+enum FontSource<'a> {
+    Reader(Box<dyn BufRead + 'a>),
+    Path(PathBuf),
+}
+
+enum PageSource {
+    Data(Vec<Vec<u8>>),
+    Paths(Vec<PathBuf>),
+    /// the files named by the `page` records, relative to the font file
+    Referenced,
 }
 
-impl AtlasBuilder for Incomplete<HieroAtlas> {
-    type AtlasType = HieroAtlas;
+/// Assembles a [`HieroAtlas`] from a .fnt file and its pages, see [`HieroAtlas::new`].\
+/// Nothing is parsed or read from disk until [`AtlasBuilder::build`], which streams the font through a [`FntReader`].
+pub struct AtlasBuilder<F> {
+    font: F,
+    pages: PageSource,
+}
 
-    fn with_font_file(mut self, fnt_file: String) -> Result<Self, &'static str> {
-        let mut table: Vec<Vec<HieroToken>> = Vec::new();
-        for line in fnt_file.lines() {
-            table.push(HieroTokenizer::tokenize_line(line));
+impl AtlasBuilder<NoFont> {
+    pub(crate) fn new() -> Self {
+        Self {
+            font: NoFont,
+            pages: PageSource::Data(Vec::new()),
         }
-        self.inner.info = parse::parse_info(&table)?;
-        self.inner.common = parse::parse_common(&table)?;
-        self.inner.bitmap_table = parse::parse_glyphs(&table)?;
-        self.inner.kerning_table = parse::parse_kerning_table(&table)?;
-        Ok(self)
+    }
+}
+
+impl<F> AtlasBuilder<F> {
+    /// the contents of a .fnt file
+    pub fn with_font_str(self, font: &str) -> AtlasBuilder<WithFont<'_>> {
+        self.with_font(FontSource::Reader(Box::new(font.as_bytes())))
+    }
+
+    /// like [`AtlasBuilder::with_font_str`] but takes ownership of the text
+    pub fn with_font_file(self, font: String) -> AtlasBuilder<WithFont<'static>> {
+        let reader = std::io::Cursor::new(font.into_bytes());
+        self.with_font(FontSource::Reader(Box::new(reader)))
+    }
+
+    /// a .fnt file on disk, read by [`AtlasBuilder::build`]
+    pub fn with_font_path<P: AsRef<Path>>(self, path: P) -> AtlasBuilder<WithFont<'static>> {
+        self.with_font(FontSource::Path(path.as_ref().to_path_buf()))
     }
 
-    fn with_pages(mut self, pages: Vec<Vec<u8>>) -> Self {
-        self.inner.compressed_pages = pages;
+    /// a .fnt file read from `reader` by [`AtlasBuilder::build`]
+    pub fn with_font_reader<'a, R: Read + 'a>(self, reader: R) -> AtlasBuilder<WithFont<'a>> {
+        self.with_font(FontSource::Reader(Box::new(BufReader::new(reader))))
+    }
+
+    /// png files of the pages, in page order
+    pub fn with_pages(mut self, pages: Vec<Vec<u8>>) -> Self {
+        self.pages = PageSource::Data(pages);
+        self
+    }
+
+    /// png files of the pages on disk, in page order, read by [`AtlasBuilder::build`]
+    pub fn with_page_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.pages = PageSource::Paths(
+            paths
+                .into_iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect(),
+        );
         self
     }
 
-    fn build(self) -> Self::AtlasType {
-        self.inner
+    /// reads the page files named in the font, relative to the font file when it was given by path
+    pub fn with_referenced_pages(mut self) -> Self {
+        self.pages = PageSource::Referenced;
+        self
+    }
+
+    fn with_font(self, font: FontSource<'_>) -> AtlasBuilder<WithFont<'_>> {
+        AtlasBuilder {
+            font: WithFont(font),
+            pages: self.pages,
+        }
+    }
+}
+
+impl<'a> AtlasBuilder<WithFont<'a>> {
+    /// streams the font and reads its pages, fails if the font lacks its `info` or `common` line
+    pub fn build(self) -> Result<HieroAtlas, Error> {
+        match self.font.0 {
+            FontSource::Reader(reader) => build(FntReader::new(reader), Path::new(""), self.pages),
+            FontSource::Path(path) => {
+                let file = std::fs::File::open(&path).map_err(|err| Error::file(&path, err))?;
+                let font_dir = path.parent().unwrap_or_else(|| Path::new(""));
+                build(FntReader::new(BufReader::new(file)), font_dir, self.pages)
+                    .map_err(|err| Error::file(&path, err))
+            }
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// assembles an atlas from the records of a font that was already parsed, `pages` in page order
+    pub(crate) fn from_records(
+        records: Vec<FntRecord>,
+        pages: Vec<Vec<u8>>,
+    ) -> Result<Self, Error> {
+        build(
+            records.into_iter().map(Ok),
+            Path::new(""),
+            PageSource::Data(pages),
+        )
+    }
+}
+
+/// Puts whatever belongs to each `page id=` at the index of its id, fails if an id is missing or
+/// declared twice since glyphs address their page by id.
+pub(crate) fn index_pages<T>(pages: Vec<(u32, T)>) -> Result<Vec<T>, ParseError> {
+    let mut indexed: Vec<Option<T>> = Vec::new();
+    indexed.resize_with(pages.len(), || None);
+    for (id, page) in pages {
+        match indexed.get_mut(id as usize) {
+            Some(slot @ None) => *slot = Some(page),
+            Some(Some(_)) => {
+                return Err(ParseError::new(
+                    None,
+                    format!("page id {} is declared twice", id),
+                ))
+            }
+            None => {
+                return Err(ParseError::new(
+                    None,
+                    format!("page id {} leaves a gap, ids have to count up from 0", id),
+                ))
+            }
+        }
     }
-}
\ No newline at end of file
+    Ok(indexed.into_iter().map(Option::unwrap).collect())
+}
+
+fn build<I: IntoIterator<Item = Result<FntRecord, Error>>>(
+    records: I,
+    font_dir: &Path,
+    pages: PageSource,
+) -> Result<HieroAtlas, Error> {
+    let mut atlas = HieroAtlas::default();
+    let (mut info, mut common) = (None, None);
+    let mut page_files = Vec::new();
+    for record in records {
+        match record? {
+            FntRecord::Info(record) => info = Some(record),
+            FntRecord::Common(record) => common = Some(record),
+            FntRecord::Page { id, file } => page_files.push((id, file)),
+            FntRecord::Char { id, bitmap } => {
+                atlas.bitmap_table.insert(id, bitmap);
+            }
+            FntRecord::Kerning {
+                first,
+                second,
+                amount,
+            } => {
+                atlas.kerning_table.insert((first, second), amount);
+            }
+        }
+    }
+    atlas.info = info.ok_or_else(|| ParseError::new(None, "'info' line missing"))?;
+    atlas.common = common.ok_or_else(|| ParseError::new(None, "'common' line missing"))?;
+
+    let pages = match pages {
+        PageSource::Data(pages) => pages,
+        PageSource::Paths(paths) => read_pages(paths)?,
+        PageSource::Referenced => read_pages(
+            index_pages(page_files)?
+                .into_iter()
+                .map(|file| font_dir.join(file)),
+        )?,
+    };
+    atlas.compressed_pages = pages.into_iter().map(CompressedPage::png).collect();
+    Ok(atlas)
+}
+
+fn read_pages<I: IntoIterator<Item = PathBuf>>(paths: I) -> Result<Vec<Vec<u8>>, Error> {
+    paths
+        .into_iter()
+        .map(|path| std::fs::read(&path).map_err(|err| Error::file(path, err)))
+        .collect()
+}
+
+#[test]
+fn build_from_every_source() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+
+    let from_str = HieroAtlas::new()
+        .with_font_str(&font_file)
+        .with_pages(vec![page_file.clone()])
+        .build()
+        .unwrap();
+    let from_reader = HieroAtlas::new()
+        .with_page_paths(vec!["./fonts/uroob.png"])
+        .with_font_reader(font_file.as_bytes())
+        .build()
+        .unwrap();
+    let from_path = HieroAtlas::new()
+        .with_font_path("./fonts/uroob.fnt")
+        .with_referenced_pages()
+        .build()
+        .unwrap();
+    for atlas in [&from_reader, &from_path].iter() {
+        assert!(AtlasDiff::new(&from_str, atlas).unwrap().is_empty());
+        assert_eq!(atlas.compressed_pages[0].data, page_file);
+    }
+
+    let missing = HieroAtlas::new()
+        .with_font_str(&font_file)
+        .with_page_paths(vec!["./fonts/missing.png"])
+        .build();
+    assert!(matches!(missing, Err(Error::File { .. })));
+    let empty = HieroAtlas::new().with_font_str("").build();
+    assert!(matches!(
+        empty,
+        Err(Error::Parse(ParseError { line: None, .. }))
+    ));
+
+    // first, second and amount used to all be read off the first field
+    let kerned = format!("{}kerning first=65 second=86 amount=-2\n", font_file);
+    let kerned = HieroAtlas::new().with_font_str(&kerned).build().unwrap();
+    assert_eq!(kerned.kerning('A', 'V'), -2);
+}
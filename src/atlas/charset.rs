@@ -0,0 +1,132 @@
+use super::*;
+
+/// A set of characters used to decide which glyphs survive a [`HieroAtlas::subset`].\
+/// Can be built from a plain string, a unicode range list (`U+0020-U+007E, U+00A9`) or a file containing either.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Charset {
+    chars: BTreeSet<char>,
+}
+
+impl Charset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// every character in `text` becomes part of the set (line breaks excluded)
+    pub fn from_chars(text: &str) -> Self {
+        let mut charset = Self::new();
+        charset.insert_str(text);
+        charset
+    }
+
+    /// parses a comma/whitespace separated list of code points and ranges.\
+    /// Accepted entries look like: `U+0041`, `0x41`, `U+0020-U+007E` or `U+0020..U+007E`
+    pub fn from_ranges(spec: &str) -> Result<Self, Error> {
+        let mut charset = Self::new();
+        for entry in spec
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+        {
+            let (lo, hi) = parse_range(entry)
+                .ok_or_else(|| Error::InvalidUnicodeRange(entry.to_string()))?;
+            charset.insert_range(lo, hi);
+        }
+        Ok(charset)
+    }
+
+    /// loads a charset file.\
+    /// Lines made up entirely of unicode ranges are read as ranges, every other line contributes its characters literally
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let text = String::from_utf8(std::fs::read(path)?)?;
+        let mut charset = Self::new();
+        for line in text.lines() {
+            match Self::from_ranges(line) {
+                Ok(ranges) if !ranges.is_empty() => charset.extend(ranges.iter()),
+                _ => charset.insert_str(line),
+            }
+        }
+        Ok(charset)
+    }
+
+    pub fn insert(&mut self, c: char) -> bool {
+        self.chars.insert(c)
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        self.chars
+            .extend(text.chars().filter(|&c| c != '\n' && c != '\r'));
+    }
+
+    /// inserts every valid scalar value in `lo..=hi`
+    pub fn insert_range(&mut self, lo: u32, hi: u32) {
+        self.chars.extend((lo..=hi).filter_map(std::char::from_u32));
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.chars.contains(&c)
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        self.chars.iter().copied()
+    }
+}
+
+impl Extend<char> for Charset {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.chars.extend(iter)
+    }
+}
+
+impl std::iter::FromIterator<char> for Charset {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self {
+            chars: iter.into_iter().collect(),
+        }
+    }
+}
+
+fn parse_range(entry: &str) -> Option<(u32, u32)> {
+    let (lo, hi) = match entry.find("..").map(|i| (i, 2)).or_else(|| {
+        // a '-' separating two code points, not a leading sign
+        entry
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '-')
+            .map(|(i, _)| (i, 1))
+    }) {
+        Some((i, sep_len)) => (&entry[..i], &entry[i + sep_len..]),
+        None => (entry, entry),
+    };
+    let lo = parse_code_point(lo)?;
+    let hi = parse_code_point(hi)?;
+    if lo <= hi {
+        Some((lo, hi))
+    } else {
+        None
+    }
+}
+
+pub(super) fn parse_code_point(text: &str) -> Option<u32> {
+    let digits = text
+        .strip_prefix("U+")
+        .or_else(|| text.strip_prefix("u+"))
+        .or_else(|| text.strip_prefix("0x"))
+        .or_else(|| text.strip_prefix("0X"))?;
+    u32::from_str_radix(digits, 16).ok()
+}
+
+#[test]
+fn charset_ranges() {
+    let charset = Charset::from_ranges("U+0041-U+0043, 0x61..0x62 U+20").unwrap();
+    assert_eq!(charset.iter().collect::<String>(), " ABCab");
+    assert!(Charset::from_ranges("U+0043-U+0041").is_err());
+    assert!(Charset::from_ranges("hello").is_err());
+}
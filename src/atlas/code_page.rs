@@ -0,0 +1,219 @@
+use super::*;
+
+/// upper half of dos code page 437, the lower half is ascii
+const OEM_437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{A0}";
+
+/// How the `id`, `first` and `second` fields of a .fnt file map to characters.\
+/// Fonts exported with `unicode=1` (or without a `charset`, which is what hiero writes) use code points,
+/// otherwise ids are the bytes of the character in the windows code page named by `charset`, lead byte first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum CodePage {
+    #[default]
+    Unicode,
+    /// ids are taken as code points, like windows does for symbol fonts
+    Symbol,
+    /// dos code page 437
+    Oem,
+    /// windows-1252, bmfont's `ANSI` and `DEFAULT`
+    Ansi,
+    ShiftJis,
+    Hangul,
+    Gb2312,
+    ChineseBig5,
+    Hebrew,
+    Arabic,
+    Greek,
+    Turkish,
+    Vietnamese,
+    Thai,
+    EastEurope,
+    Russian,
+    Baltic,
+    Mac,
+}
+
+impl CodePage {
+    /// the code page of a font, fails on charsets it doesn't know
+    pub fn from_info(info: &HieroInfo) -> Result<Self, Error> {
+        if info.unicode != 0 || info.char_set.is_empty() {
+            return Ok(Self::Unicode);
+        }
+        Self::from_charset(&info.char_set)
+            .ok_or_else(|| Error::UnsupportedCharset(info.char_set.clone()))
+    }
+
+    /// looks up a bmfont `charset` name, case doesn't matter
+    pub fn from_charset(name: &str) -> Option<Self> {
+        let code_page = match name.to_ascii_uppercase().as_str() {
+            "ANSI" | "DEFAULT" => Self::Ansi,
+            "SYMBOL" => Self::Symbol,
+            "OEM" => Self::Oem,
+            "SHIFTJIS" => Self::ShiftJis,
+            "HANGUL" => Self::Hangul,
+            "GB2312" => Self::Gb2312,
+            "CHINESEBIG5" => Self::ChineseBig5,
+            "HEBREW" => Self::Hebrew,
+            "ARABIC" => Self::Arabic,
+            "GREEK" => Self::Greek,
+            "TURKISH" => Self::Turkish,
+            "VIETNAMESE" => Self::Vietnamese,
+            "THAI" => Self::Thai,
+            "EASTEUROPE" => Self::EastEurope,
+            "RUSSIAN" => Self::Russian,
+            "BALTIC" => Self::Baltic,
+            "MAC" => Self::Mac,
+            _ => return None,
+        };
+        Some(code_page)
+    }
+
+    /// the character an id stands for, `None` for ids that aren't valid in this code page
+    pub fn decode(&self, id: u32) -> Option<char> {
+        match self {
+            Self::Unicode | Self::Symbol => std::char::from_u32(id),
+            Self::Oem => match id {
+                0..=0x7F => std::char::from_u32(id),
+                0x80..=0xFF => OEM_437_HIGH.chars().nth(id as usize - 0x80),
+                _ => None,
+            },
+            _ => self.decode_bytes(id),
+        }
+    }
+
+    /// the id `glyph` is written as, `None` if the code page lacks it
+    pub fn encode(&self, glyph: char) -> Option<u32> {
+        match self {
+            Self::Unicode | Self::Symbol => Some(glyph as u32),
+            Self::Oem if glyph.is_ascii() => Some(glyph as u32),
+            Self::Oem => OEM_437_HIGH
+                .chars()
+                .position(|c| c == glyph)
+                .map(|index| index as u32 + 0x80),
+            _ => self.encode_bytes(glyph),
+        }
+    }
+
+    #[cfg(feature = "charsets")]
+    fn encoding(&self) -> &'static encoding_rs::Encoding {
+        use encoding_rs::*;
+        match self {
+            Self::ShiftJis => SHIFT_JIS,
+            Self::Hangul => EUC_KR,
+            Self::Gb2312 => GBK,
+            Self::ChineseBig5 => BIG5,
+            Self::Hebrew => WINDOWS_1255,
+            Self::Arabic => WINDOWS_1256,
+            Self::Greek => WINDOWS_1253,
+            Self::Turkish => WINDOWS_1254,
+            Self::Vietnamese => WINDOWS_1258,
+            Self::Thai => WINDOWS_874,
+            Self::EastEurope => WINDOWS_1250,
+            Self::Russian => WINDOWS_1251,
+            Self::Baltic => WINDOWS_1257,
+            Self::Mac => MACINTOSH,
+            _ => WINDOWS_1252,
+        }
+    }
+
+    #[cfg(feature = "charsets")]
+    fn decode_bytes(&self, id: u32) -> Option<char> {
+        let bytes = match id {
+            0..=0xFF => vec![id as u8],
+            0x100..=0xFFFF => vec![(id >> 8) as u8, id as u8],
+            _ => return None,
+        };
+        let text = self
+            .encoding()
+            .decode_without_bom_handling_and_without_replacement(&bytes)?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(glyph), None) => Some(glyph),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "charsets")]
+    fn encode_bytes(&self, glyph: char) -> Option<u32> {
+        let mut buffer = [0; 4];
+        let (bytes, _, unmappable) = self.encoding().encode(glyph.encode_utf8(&mut buffer));
+        match (unmappable, &bytes[..]) {
+            (false, &[byte]) => Some(byte as u32),
+            (false, &[lead, trail]) => Some((lead as u32) << 8 | trail as u32),
+            _ => None,
+        }
+    }
+
+    // without the feature every code page still covers ascii and windows-1252 the 0xA0..=0xFF block it
+    // shares with latin-1, anything else is left undecoded
+    #[cfg(not(feature = "charsets"))]
+    fn decode_bytes(&self, id: u32) -> Option<char> {
+        Some(id)
+            .filter(|&id| self.is_native(id))
+            .and_then(std::char::from_u32)
+    }
+
+    #[cfg(not(feature = "charsets"))]
+    fn encode_bytes(&self, glyph: char) -> Option<u32> {
+        Some(glyph as u32).filter(|&id| self.is_native(id))
+    }
+
+    /// ids whose byte is the code point of their character
+    #[cfg(not(feature = "charsets"))]
+    fn is_native(&self, id: u32) -> bool {
+        match id {
+            0..=0x7F => true,
+            0xA0..=0xFF => *self == Self::Ansi,
+            _ => false,
+        }
+    }
+}
+
+#[test]
+fn decode_code_page_ids() {
+    assert_eq!(OEM_437_HIGH.chars().count(), 128);
+    let info = |char_set: &str, unicode| HieroInfo {
+        char_set: char_set.to_string(),
+        unicode,
+        ..HieroInfo::default()
+    };
+    // hiero writes `charset=""` and `unicode=0` but means code points
+    assert_eq!(
+        CodePage::from_info(&info("", 0)).unwrap(),
+        CodePage::Unicode
+    );
+    assert_eq!(
+        CodePage::from_info(&info("ANSI", 1)).unwrap(),
+        CodePage::Unicode
+    );
+    assert_eq!(
+        CodePage::from_info(&info("shiftjis", 0)).unwrap(),
+        CodePage::ShiftJis
+    );
+    assert!(matches!(
+        CodePage::from_info(&info("KLINGON", 0)),
+        Err(Error::UnsupportedCharset(name)) if name == "KLINGON"
+    ));
+
+    assert_eq!(CodePage::Unicode.decode(0x3042), Some('あ'));
+    assert_eq!(CodePage::Unicode.decode(0xD800), None);
+    assert_eq!(CodePage::Oem.decode(0xB0), Some('░'));
+    assert_eq!(CodePage::Oem.encode('░'), Some(0xB0));
+    assert_eq!(CodePage::Oem.decode(0x100), None);
+    assert_eq!(CodePage::Ansi.decode(0x41), Some('A'));
+    // windows-1252 matches latin-1 from 0xA0 on, with or without the `charsets` feature
+    assert_eq!(CodePage::Ansi.decode(0xE9), Some('é'));
+    assert_eq!(CodePage::Ansi.encode('é'), Some(0xE9));
+
+    #[cfg(feature = "charsets")]
+    {
+        assert_eq!(CodePage::Ansi.decode(0x80), Some('€'));
+        assert_eq!(CodePage::ShiftJis.decode(0x82A0), Some('あ'));
+        assert_eq!(CodePage::ShiftJis.encode('あ'), Some(0x82A0));
+        assert_eq!(CodePage::Gb2312.decode(0xC4E3), Some('你'));
+        assert_eq!(CodePage::Russian.decode(0xC0), Some('А'));
+        // a lone lead byte and an id past two bytes
+        assert_eq!(CodePage::ShiftJis.decode(0x82), None);
+        assert_eq!(CodePage::ShiftJis.decode(0x1_82A0), None);
+        assert_eq!(CodePage::Ansi.encode('あ'), None);
+    }
+}
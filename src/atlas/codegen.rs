@@ -0,0 +1,306 @@
+use super::*;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+impl HieroAtlas {
+    /// Generates a self-contained rust module that embeds the atlas, `page_paths` are the
+    /// `include_bytes!` paths of the page pngs (relative to the generated file).\
+    /// The module holds `info`/`common` constants, `GLYPHS` and `KERNING` sorted by glyph id, `NAMES` sorted by name,
+    /// the `PAGES` along with the color space and alpha mode of their texels and `glyph`/`kerning`/`glyph_by_name`
+    /// lookups that behave like [`HieroAtlas::glyph`], [`HieroAtlas::kerning`] and [`HieroAtlas::glyph_by_name`].
+    pub fn to_rust_source(&self, page_paths: &[String]) -> String {
+        let mut glyphs: Vec<(&GlyphId, &HieroBitmapInfo)> = self.bitmap_table.iter().collect();
+        glyphs.sort_by_key(|&(&id, _)| id);
+        let mut kerning: Vec<(&(GlyphId, GlyphId), &i32)> = self.kerning_table.iter().collect();
+        kerning.sort();
+
+        // writing to a String can't fail
+        let mut src = String::new();
+        let _ = writeln!(
+            src,
+            "// generated by hiero_pack from {:?}, do not edit\n",
+            self.info.face
+        );
+        src.push_str(TYPES);
+
+        let info = &self.info;
+        src.push_str("\npub mod info {\n");
+        let _ = writeln!(src, "    pub const FACE: &str = {:?};", info.face);
+        let _ = writeln!(src, "    pub const SIZE: i32 = {};", info.size);
+        let _ = writeln!(src, "    pub const BOLD: i32 = {};", info.bold);
+        let _ = writeln!(src, "    pub const ITALIC: i32 = {};", info.italic);
+        let _ = writeln!(src, "    pub const CHARSET: &str = {:?};", info.char_set);
+        let _ = writeln!(src, "    pub const UNICODE: i32 = {};", info.unicode);
+        let _ = writeln!(src, "    pub const STRETCH_H: i32 = {};", info.stretch_h);
+        let _ = writeln!(src, "    pub const SMOOTH: i32 = {};", info.smooth);
+        let _ = writeln!(src, "    pub const AA: i32 = {};", info.aa);
+        let _ = writeln!(src, "    pub const PADDING: &[i32] = &{:?};", info.padding);
+        let _ = writeln!(src, "    pub const SPACING: &[i32] = &{:?};", info.spacing);
+        let _ = writeln!(
+            src,
+            "    pub const OUTLINE: Option<i32> = {:?};",
+            info.outline
+        );
+        src.push_str("}\n");
+
+        let common = &self.common;
+        src.push_str("\npub mod common {\n");
+        let _ = writeln!(
+            src,
+            "    pub const LINE_HEIGHT: i32 = {};",
+            common.line_height
+        );
+        let _ = writeln!(src, "    pub const BASE: i32 = {};", common.base);
+        let _ = writeln!(src, "    pub const SCALE_W: i32 = {};", common.scale_w);
+        let _ = writeln!(src, "    pub const SCALE_H: i32 = {};", common.scale_h);
+        let _ = writeln!(src, "    pub const PAGES: i32 = {};", common.pages);
+        let _ = writeln!(src, "    pub const PACKED: i32 = {};", common.packed);
+        let _ = writeln!(
+            src,
+            "    pub const ALPHA_CHNL: Option<i32> = {:?};",
+            common.alpha_chnl
+        );
+        let _ = writeln!(
+            src,
+            "    pub const RED_CHNL: Option<i32> = {:?};",
+            common.red_chnl
+        );
+        let _ = writeln!(
+            src,
+            "    pub const GREEN_CHNL: Option<i32> = {:?};",
+            common.green_chnl
+        );
+        let _ = writeln!(
+            src,
+            "    pub const BLUE_CHNL: Option<i32> = {:?};",
+            common.blue_chnl
+        );
+        src.push_str("}\n");
+
+        src.push_str("\n/// sorted by `id`\npub const GLYPHS: &[Glyph] = &[\n");
+        for (id, glyph) in glyphs {
+            let _ = writeln!(
+                src,
+                "    Glyph {{ id: {:#06x}, x: {}, y: {}, width: {}, height: {}, xoffset: {}, yoffset: {}, xadvance: {}, page: {}, channel: {} }},",
+                id.0,
+                glyph.x,
+                glyph.y,
+                glyph.width,
+                glyph.height,
+                glyph.xoffset,
+                glyph.yoffset,
+                glyph.xadvance,
+                glyph.page,
+                glyph.channel
+            );
+        }
+        src.push_str("];\n");
+
+        src.push_str("\n/// `(first, second, amount)` sorted by pair\npub const KERNING: &[(u32, u32, i32)] = &[\n");
+        for ((first, second), amount) in kerning {
+            let _ = writeln!(
+                src,
+                "    ({:#06x}, {:#06x}, {}),",
+                first.0, second.0, amount
+            );
+        }
+        src.push_str("];\n");
+
+        src.push_str("\n/// `(name, id)` sorted by name\npub const NAMES: &[(&str, u32)] = &[\n");
+        for (name, id) in self.glyph_names.iter() {
+            let _ = writeln!(src, "    ({:?}, {:#06x}),", name, id.0);
+        }
+        src.push_str("];\n");
+
+        src.push_str("\npub const PAGES: &[Page] = &[\n");
+        for (page_index, path) in page_paths.iter().enumerate() {
+            let info = self
+                .compressed_pages
+                .get(page_index)
+                .map_or_else(PageInfo::default, |page| page.info);
+            let _ = writeln!(
+                src,
+                "    Page {{ png: include_bytes!({:?}), color_space: ColorSpace::{:?}, alpha_mode: AlphaMode::{:?} }},",
+                path, info.color_space, info.alpha_mode
+            );
+        }
+        src.push_str("];\n");
+
+        src.push_str(LOOKUP_FNS);
+        src
+    }
+
+    /// Writes `{name}.rs` and one `{name}_page{i}.png` per page to `dir`, returns the path of the module.\
+    /// Meant for `build.rs`, the module can then be pulled in with
+    /// `include!(concat!(env!("OUT_DIR"), "/{name}.rs"))` since `include_bytes!` resolves the pages next to it.
+    pub fn write_rust_module<P: AsRef<Path>>(&self, dir: P, name: &str) -> Result<PathBuf, Error> {
+        let dir = dir.as_ref();
+        let mut page_names = Vec::with_capacity(self.compressed_pages.len());
+        for (page_index, page) in self.compressed_pages.iter().enumerate() {
+            let page_name = format!("{}_page{}.png", name, page_index);
+            std::fs::write(dir.join(&page_name), page.to_png()?)?;
+            page_names.push(page_name);
+        }
+        let path = dir.join(format!("{}.rs", name));
+        std::fs::write(&path, self.to_rust_source(&page_names))?;
+        Ok(path)
+    }
+}
+
+const TYPES: &str = "\
+/// mirrors `hiero_pack::GlyphId`, `char`s and `u32`s convert into it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GlyphId(pub u32);
+
+impl From<char> for GlyphId {
+    fn from(c: char) -> Self {
+        GlyphId(c as u32)
+    }
+}
+
+impl From<u32> for GlyphId {
+    fn from(id: u32) -> Self {
+        GlyphId(id)
+    }
+}
+
+/// mirrors `hiero_pack::HieroBitmapInfo`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub xoffset: i32,
+    pub yoffset: i32,
+    pub xadvance: i32,
+    pub page: i32,
+    pub channel: i32,
+}
+
+/// mirrors `hiero_pack::ColorSpace`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+/// mirrors `hiero_pack::AlphaMode`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    Straight,
+    Premultiplied,
+}
+
+/// a page as a png file along with the conventions its texels are stored in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    pub png: &'static [u8],
+    pub color_space: ColorSpace,
+    pub alpha_mode: AlphaMode,
+}
+";
+
+const LOOKUP_FNS: &str = "
+/// the glyph of a character or `GlyphId`
+pub fn glyph<G: Into<GlyphId>>(glyph: G) -> Option<&'static Glyph> {
+    let id = glyph.into().0;
+    GLYPHS
+        .binary_search_by_key(&id, |glyph| glyph.id)
+        .ok()
+        .map(|index| &GLYPHS[index])
+}
+
+/// the kerning amount between two glyphs, 0 if the pair isn't kerned
+pub fn kerning<G: Into<GlyphId>>(first: G, second: G) -> i32 {
+    let pair = (first.into().0, second.into().0);
+    KERNING
+        .binary_search_by_key(&pair, |&(a, b, _)| (a, b))
+        .map_or(0, |index| KERNING[index].2)
+}
+
+/// the glyph named `name`
+pub fn glyph_by_name(name: &str) -> Option<&'static Glyph> {
+    NAMES
+        .binary_search_by_key(&name, |&(name, _)| name)
+        .ok()
+        .and_then(|index| glyph(NAMES[index].1))
+}
+";
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod generated {
+    include!("../../fixtures/codegen/tiny.rs");
+}
+
+#[cfg(test)]
+fn tiny_atlas() -> HieroAtlas {
+    let glyph = |x, xadvance| HieroBitmapInfo {
+        x,
+        width: 2,
+        height: 3,
+        xadvance,
+        channel: 15,
+        ..HieroBitmapInfo::default()
+    };
+    let mut atlas = HieroAtlas::default();
+    atlas.info.face = String::from("Tiny \"Test\"");
+    atlas.info.padding = vec![0, 0, 0, 0];
+    atlas.info.spacing = vec![1, 1];
+    atlas.common.line_height = 4;
+    atlas.common.base = 3;
+    atlas.common.scale_w = 8;
+    atlas.common.scale_h = 4;
+    atlas.common.pages = 1;
+    atlas.common.alpha_chnl = Some(0);
+    atlas.bitmap_table.insert('b'.into(), glyph(3, 3));
+    atlas.bitmap_table.insert('a'.into(), glyph(0, 3));
+    atlas.bitmap_table.insert('\''.into(), glyph(6, 1));
+    atlas.bitmap_table.insert(GlyphId(0xD800), glyph(0, 1));
+    atlas.kerning_table.insert(('a'.into(), 'b'.into()), -1);
+    atlas.kerning_table.insert(('\''.into(), 'a'.into()), 1);
+    atlas.glyph_names.insert("quote", '\'');
+    atlas.glyph_names.insert("icon", GlyphId(0xD800));
+    atlas.compressed_pages.push(CompressedPage {
+        info: PageInfo {
+            alpha_mode: AlphaMode::Premultiplied,
+            ..PageInfo::default()
+        },
+        ..CompressedPage::png(include_bytes!("../../fixtures/codegen/tiny_page0.png").to_vec())
+    });
+    atlas
+}
+
+#[test]
+fn generated_module_matches_fixture() {
+    // regenerate the fixture with write_rust_module if the output format changes on purpose
+    let source = tiny_atlas().to_rust_source(&[String::from("tiny_page0.png")]);
+    assert_eq!(source, include_str!("../../fixtures/codegen/tiny.rs"));
+
+    let atlas = tiny_atlas();
+    for (id, bitmap) in atlas.bitmap_table.iter() {
+        let glyph = generated::glyph(generated::GlyphId(id.0)).unwrap();
+        assert_eq!((glyph.x, glyph.xadvance), (bitmap.x, bitmap.xadvance));
+    }
+    assert!(generated::glyph('c').is_none());
+    assert_eq!(generated::glyph_by_name("quote").unwrap().x, 6);
+    assert_eq!(generated::glyph_by_name("icon").unwrap().id, 0xD800);
+    assert_eq!(generated::glyph(0xD800).unwrap().xadvance, 1);
+    assert_eq!(generated::kerning('a', 'b'), atlas.kerning('a', 'b'));
+    assert_eq!(generated::kerning('b', 'a'), 0);
+    assert_eq!(generated::common::LINE_HEIGHT, 4);
+    assert_eq!(generated::common::ALPHA_CHNL, Some(0));
+    assert_eq!(generated::info::OUTLINE, None);
+    assert_eq!(generated::info::FACE, "Tiny \"Test\"");
+    assert_eq!(
+        generated::PAGES[0].png,
+        &include_bytes!("../../fixtures/codegen/tiny_page0.png")[..]
+    );
+    assert_eq!(generated::PAGES[0].color_space, generated::ColorSpace::Srgb);
+    assert_eq!(
+        generated::PAGES[0].alpha_mode,
+        generated::AlphaMode::Premultiplied
+    );
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+/// The transfer function page texels are encoded with
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// what hiero writes
+    #[default]
+    Srgb,
+    Linear,
+}
+
+/// Whether the color channels of a page have been multiplied by alpha
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// what hiero writes
+    #[default]
+    Straight,
+    Premultiplied,
+}
+
+/// Conventions a page should be converted to while unpacking, `None` keeps whatever is stored.\
+/// Every conversion works on 8-bit texels, so going from srgb to linear and back is lossy.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UnpackOptions {
+    pub color_space: Option<ColorSpace>,
+    pub alpha_mode: Option<AlphaMode>,
+    /// expand gray and rgb pages to 4 samples per pixel
+    pub force_rgba: bool,
+}
+
+impl HieroAtlas {
+    /// decodes a compressed page and converts it to the conventions asked for in `options`
+    pub fn try_unpack_page_with(
+        &self,
+        page_index: usize,
+        options: &UnpackOptions,
+    ) -> Result<HieroPage, Error> {
+        Ok(self.try_unpack_page(page_index)?.convert(options))
+    }
+
+    /// converts the stored pages (and their mips) to another color space/alpha mode,
+    /// re-encoding them with the encoding they already had
+    pub fn convert_pages(
+        &mut self,
+        color_space: ColorSpace,
+        alpha_mode: AlphaMode,
+    ) -> Result<(), Error> {
+        let options = UnpackOptions {
+            color_space: Some(color_space),
+            alpha_mode: Some(alpha_mode),
+            force_rgba: false,
+        };
+        for page in self
+            .compressed_pages
+            .iter_mut()
+            .chain(self.mip_pages.iter_mut().flatten())
+        {
+            if page.info.color_space == color_space && page.info.alpha_mode == alpha_mode {
+                continue;
+            }
+            *page = CompressedPage::encode(&page.decode()?.convert(&options), page.encoding)?;
+        }
+        Ok(())
+    }
+}
+
+impl HieroPage {
+    /// returns a copy that follows the conventions asked for in `options`
+    pub fn convert(&self, options: &UnpackOptions) -> HieroPage {
+        let mut page = if options.force_rgba {
+            self.to_rgba()
+        } else {
+            self.clone()
+        };
+        let info = page.info();
+        let color_space = options.color_space.unwrap_or(info.color_space);
+        let alpha_mode = options.alpha_mode.unwrap_or(info.alpha_mode);
+        let samples = info.samples as usize;
+        let has_alpha = samples == 2 || samples == 4;
+        let color_samples = if has_alpha { samples - 1 } else { samples };
+
+        let transfer_changes = color_space != info.color_space;
+        let alpha_changes = has_alpha && alpha_mode != info.alpha_mode;
+        if transfer_changes || alpha_changes {
+            for px in page.pixels_mut().chunks_mut(samples) {
+                let alpha = if has_alpha {
+                    px[samples - 1] as f32 / 255.0
+                } else {
+                    1.0
+                };
+                for value in px[..color_samples].iter_mut() {
+                    let mut c = *value as f32 / 255.0;
+                    if has_alpha && info.alpha_mode == AlphaMode::Premultiplied {
+                        c = if alpha > 0.0 {
+                            (c / alpha).min(1.0)
+                        } else {
+                            0.0
+                        };
+                    }
+                    if transfer_changes {
+                        c = match color_space {
+                            ColorSpace::Linear => srgb_to_linear(c),
+                            ColorSpace::Srgb => linear_to_srgb(c),
+                        };
+                    }
+                    if has_alpha && alpha_mode == AlphaMode::Premultiplied {
+                        c *= alpha;
+                    }
+                    *value = (c * 255.0).round() as u8;
+                }
+            }
+        }
+        page.with_conventions(color_space, alpha_mode)
+    }
+}
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[test]
+fn premultiply_round_trip() {
+    let page = HieroPage::blank(2, 1, 4).with_pixels(vec![255, 128, 0, 128, 10, 20, 30, 0]);
+    let premultiplied = page.convert(&UnpackOptions {
+        alpha_mode: Some(AlphaMode::Premultiplied),
+        ..UnpackOptions::default()
+    });
+    assert_eq!(premultiplied.info().alpha_mode, AlphaMode::Premultiplied);
+    assert_eq!(premultiplied.pixels()[..], [128, 64, 0, 128, 0, 0, 0, 0]);
+
+    let straight = premultiplied.convert(&UnpackOptions {
+        alpha_mode: Some(AlphaMode::Straight),
+        ..UnpackOptions::default()
+    });
+    assert_eq!(straight.pixels()[..4], [255, 128, 0, 128]);
+
+    let linear = HieroPage::blank(1, 1, 1)
+        .with_pixels(vec![188])
+        .convert(&UnpackOptions {
+            color_space: Some(ColorSpace::Linear),
+            force_rgba: true,
+            ..UnpackOptions::default()
+        });
+    assert_eq!(linear.info().samples, 4);
+    assert_eq!(linear.pixels()[..], [128, 128, 128, 255]);
+}
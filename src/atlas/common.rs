@@ -1,7 +1,7 @@
 use super::*; 
 
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct HieroCommon {
     pub line_height: i32,
     pub base: i32,
@@ -9,4 +9,57 @@ pub struct HieroCommon {
     pub scale_h: i32,
     pub pages: i32,
     pub packed: i32,
+    /// what the alpha channel of the pages holds, see [`ChannelContent`]. Hiero leaves these out
+    pub alpha_chnl: Option<i32>,
+    pub red_chnl: Option<i32>,
+    pub green_chnl: Option<i32>,
+    pub blue_chnl: Option<i32>,
+}
+
+impl HieroCommon {
+    /// what the page channel `index` (0 = red, 1 = green, 2 = blue, 3 = alpha) holds, `None` if the font doesn't say
+    pub fn channel_content(&self, index: usize) -> Option<ChannelContent> {
+        let value = match index {
+            0 => self.red_chnl,
+            1 => self.green_chnl,
+            2 => self.blue_chnl,
+            3 => self.alpha_chnl,
+            _ => None,
+        };
+        value.and_then(ChannelContent::from_bmfont)
+    }
+}
+
+/// The meaning bmfont gives the values of `alphaChnl`, `redChnl`, `greenChnl` and `blueChnl`
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChannelContent {
+    Glyph,
+    Outline,
+    /// the glyph above 128 and its outline below
+    GlyphAndOutline,
+    Zero,
+    One,
+}
+
+impl ChannelContent {
+    pub fn from_bmfont(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Glyph),
+            1 => Some(Self::Outline),
+            2 => Some(Self::GlyphAndOutline),
+            3 => Some(Self::Zero),
+            4 => Some(Self::One),
+            _ => None,
+        }
+    }
+
+    pub fn to_bmfont(self) -> i32 {
+        match self {
+            Self::Glyph => 0,
+            Self::Outline => 1,
+            Self::GlyphAndOutline => 2,
+            Self::Zero => 3,
+            Self::One => 4,
+        }
+    }
 }
\ No newline at end of file
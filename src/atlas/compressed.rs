@@ -0,0 +1,272 @@
+use super::*;
+
+/// How the bytes of a [`CompressedPage`] are stored
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PageEncoding {
+    /// the png file as written by hiero (or as re-encoded by [`HieroAtlas::optimize_pages`])
+    Png,
+    /// uncompressed pixels, fastest to load
+    Raw,
+    /// lz4 compressed pixels (needs the `lz4` feature)
+    Lz4,
+    /// zstd compressed pixels (needs the `zstd` feature)
+    Zstd,
+    /// a qoi image (needs the `qoi` feature)
+    Qoi,
+}
+
+impl PageEncoding {
+    pub const ALL: [PageEncoding; 5] = [Self::Png, Self::Raw, Self::Lz4, Self::Zstd, Self::Qoi];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Raw => "raw",
+            Self::Lz4 => "lz4",
+            Self::Zstd => "zstd",
+            Self::Qoi => "qoi",
+        }
+    }
+
+    /// false if the crate was built without the feature this encoding needs
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::Png | Self::Raw => true,
+            Self::Lz4 => cfg!(feature = "lz4"),
+            Self::Zstd => cfg!(feature = "zstd"),
+            Self::Qoi => cfg!(feature = "qoi"),
+        }
+    }
+}
+
+impl std::fmt::Display for PageEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for PageEncoding {
+    type Err = Error;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|encoding| encoding.name() == name)
+            .ok_or_else(|| Error::UnknownPageEncoding(name.to_string()))
+    }
+}
+
+/// A page as it is stored inside of the atlas.\
+/// `info` describes the decoded pixels, png and qoi pages carry their own header so it is only
+/// needed to make sense of the raw, lz4 and zstd encodings.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CompressedPage {
+    pub encoding: PageEncoding,
+    pub info: PageInfo,
+    pub data: Vec<u8>,
+}
+
+impl CompressedPage {
+    /// wraps png bytes without decoding them
+    pub fn png(data: Vec<u8>) -> Self {
+        Self {
+            encoding: PageEncoding::Png,
+            info: PageInfo::default(),
+            data,
+        }
+    }
+
+    pub fn encode(page: &HieroPage, encoding: PageEncoding) -> Result<Self, Error> {
+        if page.palette().is_some() && encoding != PageEncoding::Png {
+            return Err(Error::IndexedPageEncoding(encoding));
+        }
+        let pixels = page.pixels();
+        let data = match encoding {
+            PageEncoding::Png => page.encode_png()?,
+            PageEncoding::Raw => pixels.clone(),
+            #[cfg(feature = "lz4")]
+            PageEncoding::Lz4 => lz4_flex::compress_prepend_size(pixels),
+            #[cfg(feature = "zstd")]
+            PageEncoding::Zstd => zstd::bulk::compress(pixels, 19)?,
+            #[cfg(feature = "qoi")]
+            PageEncoding::Qoi => {
+                let info = page.info();
+                let pixels = widen_gray(pixels, info.samples);
+                qoi::encode_to_vec(&pixels, info.width, info.height).map_err(Error::QoiEncode)?
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => {
+                return Err(Error::PageUnpack(PageUnpackError::UnsupportedEncoding(
+                    unsupported,
+                )))
+            }
+        };
+        Ok(Self {
+            encoding,
+            info: page.info(),
+            data,
+        })
+    }
+
+    /// decodes the page, the result carries the color space and alpha mode recorded in `info`
+    pub fn decode(&self) -> Result<HieroPage, Error> {
+        self.decode_with(PngTransform::NORMALIZE)
+    }
+
+    /// like [`CompressedPage::decode`], `transform` only matters for png pages
+    pub fn decode_with(&self, transform: PngTransform) -> Result<HieroPage, Error> {
+        let expected_len = (self.info.line_size * self.info.height) as usize;
+        let raw_page = |pixels: Vec<u8>| {
+            if pixels.len() == expected_len {
+                Ok(HieroPage::new(self.info, pixels))
+            } else {
+                Err(corrupt_page(format!(
+                    "expected {} bytes of pixels, found {}",
+                    expected_len,
+                    pixels.len()
+                )))
+            }
+        };
+        let page = match self.encoding {
+            PageEncoding::Png => HieroPage::decode_png_with(&self.data, transform),
+            PageEncoding::Raw => raw_page(self.data.clone()),
+            #[cfg(feature = "lz4")]
+            PageEncoding::Lz4 => lz4_flex::decompress_size_prepended(&self.data)
+                .map_err(corrupt_page)
+                .and_then(raw_page),
+            #[cfg(feature = "zstd")]
+            PageEncoding::Zstd => zstd::bulk::decompress(&self.data, expected_len)
+                .map_err(corrupt_page)
+                .and_then(raw_page),
+            #[cfg(feature = "qoi")]
+            PageEncoding::Qoi => {
+                let (header, pixels) = qoi::decode_to_vec(&self.data).map_err(corrupt_page)?;
+                let (samples, pixels) =
+                    narrow_gray(pixels, header.channels.as_u8() as u32, self.info.samples);
+                let info = PageInfo {
+                    width: header.width,
+                    height: header.height,
+                    samples,
+                    line_size: header.width * samples,
+                    color_type: PageColorType::from_samples(samples),
+                    ..PageInfo::default()
+                };
+                Ok(HieroPage::new(info, pixels))
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => Err(PageUnpackError::UnsupportedEncoding(unsupported).into()),
+        };
+        page.map(|page| page.with_conventions(self.info.color_space, self.info.alpha_mode))
+    }
+
+    /// reads the width and height of the page without decoding its pixels
+    pub fn dimensions(&self) -> Result<(u32, u32), Error> {
+        match self.encoding {
+            PageEncoding::Png => {
+                let (info, _) = png::Decoder::new(&self.data[..])
+                    .read_info()
+                    .map_err(PageUnpackError::PageDecodeError)?;
+                Ok((info.width, info.height))
+            }
+            #[cfg(feature = "qoi")]
+            PageEncoding::Qoi => qoi::decode_header(&self.data)
+                .map(|header| (header.width, header.height))
+                .map_err(corrupt_page),
+            _ if self.encoding.is_supported() => Ok((self.info.width, self.info.height)),
+            unsupported => Err(PageUnpackError::UnsupportedEncoding(unsupported).into()),
+        }
+    }
+
+    /// the page as a png file, png pages are handed back as they are stored
+    pub fn to_png(&self) -> Result<Vec<u8>, Error> {
+        match self.encoding {
+            PageEncoding::Png => Ok(self.data.clone()),
+            _ => self.decode()?.encode_png(),
+        }
+    }
+
+    /// decodes and re-encodes the page, returns a clone if the encoding doesn't change.\
+    /// Mips of an atlas page are left as they are, see [`HieroAtlas::reencode_pages`]
+    pub fn reencode(&self, encoding: PageEncoding) -> Result<Self, Error> {
+        if encoding == self.encoding {
+            Ok(self.clone())
+        } else {
+            Self::encode(&self.decode()?, encoding)
+        }
+    }
+}
+
+/// qoi only stores rgb(a), gray and gray+alpha pixels are spread over rgb(a) on the way in
+#[cfg(feature = "qoi")]
+fn widen_gray(pixels: &[u8], samples: u32) -> std::borrow::Cow<'_, [u8]> {
+    match samples {
+        1 => pixels.iter().flat_map(|&gray| [gray; 3]).collect(),
+        2 => pixels
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        _ => pixels.into(),
+    }
+}
+
+/// undoes [`widen_gray`] for pages that were stored with `stored_samples`, returns the samples per pixel
+#[cfg(feature = "qoi")]
+fn narrow_gray(pixels: Vec<u8>, channels: u32, stored_samples: u32) -> (u32, Vec<u8>) {
+    match (channels, stored_samples) {
+        (3, 1) => (1, pixels.chunks_exact(3).map(|pixel| pixel[0]).collect()),
+        (4, 2) => (
+            2,
+            pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[0], pixel[3]])
+                .collect(),
+        ),
+        _ => (channels, pixels),
+    }
+}
+
+fn corrupt_page<E: ToString>(reason: E) -> Error {
+    Error::PageUnpack(PageUnpackError::CorruptPage(reason.to_string()))
+}
+
+#[test]
+fn page_encodings_round_trip() {
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let page = HieroPage::decode_png(&page_file).unwrap();
+    for &encoding in PageEncoding::ALL.iter().filter(|e| e.is_supported()) {
+        let compressed = CompressedPage::encode(&page, encoding).unwrap();
+        let decoded = compressed.decode().unwrap();
+        assert_eq!(decoded.info().samples, page.info().samples, "{}", encoding);
+        assert_eq!(decoded.pixels(), page.pixels(), "{}", encoding);
+    }
+
+    // qoi has no gray formats, gray pages still come back gray
+    let gray = HieroPage::decode_png(&std::fs::read("./fixtures/png/gray8.png").unwrap()).unwrap();
+    let gray_alpha = HieroPage::new(
+        PageInfo {
+            width: 2,
+            height: 1,
+            samples: 2,
+            line_size: 4,
+            color_type: PageColorType::GrayscaleAlpha,
+            ..PageInfo::default()
+        },
+        vec![10, 255, 200, 0],
+    );
+    for page in [gray, gray_alpha].iter() {
+        for &encoding in PageEncoding::ALL.iter().filter(|e| e.is_supported()) {
+            let decoded = CompressedPage::encode(page, encoding)
+                .unwrap()
+                .decode()
+                .unwrap();
+            assert_eq!(decoded.info().samples, page.info().samples, "{}", encoding);
+            assert_eq!(
+                decoded.info().color_type,
+                page.info().color_type,
+                "{}",
+                encoding
+            );
+            assert_eq!(decoded.pixels(), page.pixels(), "{}", encoding);
+        }
+    }
+}
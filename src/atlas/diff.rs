@@ -0,0 +1,343 @@
+use super::{draw::glyph_texel, *};
+
+/// A field whose value differs between two atlases, values are rendered with `Debug`
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Metric fields of a glyph that changed
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct GlyphChange {
+    pub glyph: GlyphId,
+    pub fields: Vec<FieldChange>,
+}
+
+/// How much the bitmap of a glyph present in both atlases changed.\
+/// Only compared when the glyph kept its size, a resized glyph shows up in [`AtlasDiff::metrics`] instead.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PixelChange {
+    pub glyph: GlyphId,
+    pub differing_texels: u32,
+    pub total_texels: u32,
+    /// largest difference of a single channel
+    pub max_delta: u8,
+}
+
+/// A kerning pair that was added (`before` is `None`), removed (`after` is `None`) or changed
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct KerningChange {
+    pub first: GlyphId,
+    pub second: GlyphId,
+    pub before: Option<i32>,
+    pub after: Option<i32>,
+}
+
+/// Everything that differs between two atlases, see [`AtlasDiff::new`]
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AtlasDiff {
+    pub info: Vec<FieldChange>,
+    pub common: Vec<FieldChange>,
+    pub added_glyphs: Vec<GlyphId>,
+    pub removed_glyphs: Vec<GlyphId>,
+    pub metrics: Vec<GlyphChange>,
+    pub pixels: Vec<PixelChange>,
+    pub kerning: Vec<KerningChange>,
+}
+
+impl AtlasDiff {
+    /// Compares `before` against `after`, every list is sorted by glyph id.\
+    /// Glyph bitmaps are only compared when both atlases carry pages, moving a glyph
+    /// to another spot of the page doesn't count as a change as long as its texels stay the same.
+    /// Pages are compared as straight srgb, so storing them linear or premultiplied isn't a change either.
+    pub fn new(before: &HieroAtlas, after: &HieroAtlas) -> Result<Self, Error> {
+        let mut diff = Self::default();
+
+        let (a, b) = (&before.info, &after.info);
+        let info = &mut diff.info;
+        compare(info, "face", &a.face, &b.face);
+        compare(info, "size", &a.size, &b.size);
+        compare(info, "bold", &a.bold, &b.bold);
+        compare(info, "italic", &a.italic, &b.italic);
+        compare(info, "charset", &a.char_set, &b.char_set);
+        compare(info, "unicode", &a.unicode, &b.unicode);
+        compare(info, "stretchH", &a.stretch_h, &b.stretch_h);
+        compare(info, "smooth", &a.smooth, &b.smooth);
+        compare(info, "aa", &a.aa, &b.aa);
+        compare(info, "padding", &a.padding, &b.padding);
+        compare(info, "spacing", &a.spacing, &b.spacing);
+        compare(info, "outline", &a.outline, &b.outline);
+
+        let (a, b) = (&before.common, &after.common);
+        let common = &mut diff.common;
+        compare(common, "lineHeight", &a.line_height, &b.line_height);
+        compare(common, "base", &a.base, &b.base);
+        compare(common, "scaleW", &a.scale_w, &b.scale_w);
+        compare(common, "scaleH", &a.scale_h, &b.scale_h);
+        compare(common, "pages", &a.pages, &b.pages);
+        compare(common, "packed", &a.packed, &b.packed);
+        compare(common, "alphaChnl", &a.alpha_chnl, &b.alpha_chnl);
+        compare(common, "redChnl", &a.red_chnl, &b.red_chnl);
+        compare(common, "greenChnl", &a.green_chnl, &b.green_chnl);
+        compare(common, "blueChnl", &a.blue_chnl, &b.blue_chnl);
+
+        let glyphs: BTreeSet<GlyphId> = before
+            .bitmap_table
+            .keys()
+            .chain(after.bitmap_table.keys())
+            .copied()
+            .collect();
+        let unpack_all = |atlas: &HieroAtlas| {
+            (0..atlas.compressed_pages.len())
+                .map(|page_index| {
+                    atlas.try_unpack_page_with(
+                        page_index,
+                        &UnpackOptions {
+                            color_space: Some(ColorSpace::Srgb),
+                            alpha_mode: Some(AlphaMode::Straight),
+                            force_rgba: true,
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let (pages_before, pages_after) = (unpack_all(before)?, unpack_all(after)?);
+
+        for glyph in glyphs {
+            let (a, b) = match (
+                before.bitmap_table.get(&glyph),
+                after.bitmap_table.get(&glyph),
+            ) {
+                (Some(a), Some(b)) => (a, b),
+                (None, _) => {
+                    diff.added_glyphs.push(glyph);
+                    continue;
+                }
+                (_, None) => {
+                    diff.removed_glyphs.push(glyph);
+                    continue;
+                }
+            };
+
+            let mut fields = Vec::new();
+            compare(&mut fields, "width", &a.width, &b.width);
+            compare(&mut fields, "height", &a.height, &b.height);
+            compare(&mut fields, "xoffset", &a.xoffset, &b.xoffset);
+            compare(&mut fields, "yoffset", &a.yoffset, &b.yoffset);
+            compare(&mut fields, "xadvance", &a.xadvance, &b.xadvance);
+            if !fields.is_empty() {
+                diff.metrics.push(GlyphChange { glyph, fields });
+            }
+
+            let pages = (
+                pages_before.get(a.page as usize),
+                pages_after.get(b.page as usize),
+            );
+            if let (Some(page_a), Some(page_b)) = pages {
+                if (a.width, a.height) == (b.width, b.height) {
+                    if let Some(change) = compare_pixels(
+                        glyph,
+                        (page_a, a, &before.common),
+                        (page_b, b, &after.common),
+                    ) {
+                        diff.pixels.push(change);
+                    }
+                }
+            }
+        }
+
+        let pairs: BTreeSet<(GlyphId, GlyphId)> = before
+            .kerning_table
+            .keys()
+            .chain(after.kerning_table.keys())
+            .copied()
+            .collect();
+        for (first, second) in pairs {
+            let amounts = (
+                before.kerning_table.get(&(first, second)).copied(),
+                after.kerning_table.get(&(first, second)).copied(),
+            );
+            if amounts.0 != amounts.1 {
+                diff.kerning.push(KerningChange {
+                    first,
+                    second,
+                    before: amounts.0,
+                    after: amounts.1,
+                });
+            }
+        }
+
+        Ok(diff)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn compare<T: PartialEq + std::fmt::Debug>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    before: &T,
+    after: &T,
+) {
+    if before != after {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            before: format!("{:?}", before),
+            after: format!("{:?}", after),
+        });
+    }
+}
+
+/// compares the texels of a glyph that has the same size in both atlases
+fn compare_pixels(
+    glyph: GlyphId,
+    (page_a, a, common_a): (&HieroPage, &HieroBitmapInfo, &HieroCommon),
+    (page_b, b, common_b): (&HieroPage, &HieroBitmapInfo, &HieroCommon),
+) -> Option<PixelChange> {
+    let inside = |page: &HieroPage, bitmap: &HieroBitmapInfo| {
+        let info = page.info();
+        bitmap.x >= 0
+            && bitmap.y >= 0
+            && (bitmap.x + bitmap.width) as u32 <= info.width
+            && (bitmap.y + bitmap.height) as u32 <= info.height
+    };
+    if !inside(page_a, a) || !inside(page_b, b) {
+        return None;
+    }
+
+    let (mut differing_texels, mut max_delta) = (0, 0);
+    for y in 0..a.height {
+        for x in 0..a.width {
+            let texel_a = glyph_texel(page_a, a, common_a, (a.x + x) as u32, (a.y + y) as u32);
+            let texel_b = glyph_texel(page_b, b, common_b, (b.x + x) as u32, (b.y + y) as u32);
+            let delta = texel_a
+                .iter()
+                .zip(texel_b.iter())
+                .map(|(&ca, &cb)| (ca as i32 - cb as i32).unsigned_abs() as u8)
+                .max()
+                .unwrap_or(0);
+            if delta > 0 {
+                differing_texels += 1;
+                max_delta = max_delta.max(delta);
+            }
+        }
+    }
+    if differing_texels == 0 {
+        return None;
+    }
+    Some(PixelChange {
+        glyph,
+        differing_texels,
+        total_texels: (a.width * a.height) as u32,
+        max_delta,
+    })
+}
+
+impl std::fmt::Display for AtlasDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let amount = |amount: Option<i32>| match amount {
+            Some(amount) => amount.to_string(),
+            None => String::from("none"),
+        };
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for (section, changes) in [("info", &self.info), ("common", &self.common)].iter() {
+            for change in changes.iter() {
+                writeln!(
+                    f,
+                    "~ {}.{}: {} -> {}",
+                    section, change.field, change.before, change.after
+                )?;
+            }
+        }
+        for &glyph in self.added_glyphs.iter() {
+            writeln!(f, "+ glyph {}", glyph.describe())?;
+        }
+        for &glyph in self.removed_glyphs.iter() {
+            writeln!(f, "- glyph {}", glyph.describe())?;
+        }
+        for change in self.metrics.iter() {
+            let fields: Vec<String> = change
+                .fields
+                .iter()
+                .map(|field| format!("{} {} -> {}", field.field, field.before, field.after))
+                .collect();
+            writeln!(
+                f,
+                "~ glyph {}: {}",
+                change.glyph.describe(),
+                fields.join(", ")
+            )?;
+        }
+        for change in self.pixels.iter() {
+            writeln!(
+                f,
+                "~ glyph {}: {} of {} texels differ (max delta {})",
+                change.glyph.describe(),
+                change.differing_texels,
+                change.total_texels,
+                change.max_delta
+            )?;
+        }
+        for change in self.kerning.iter() {
+            writeln!(
+                f,
+                "~ kerning {} {}: {} -> {}",
+                change.first.describe(),
+                change.second.describe(),
+                amount(change.before),
+                amount(change.after)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn diff_atlases() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let before = HieroAtlas::new()
+        .with_font_file(font_file)
+        .with_pages(vec![page_file])
+        .build()
+        .unwrap();
+    assert!(AtlasDiff::new(&before, &before).unwrap().is_empty());
+
+    let mut after = before.clone();
+    after.info.size = 40;
+    after.bitmap_table.remove(&'z'.into());
+    after
+        .bitmap_table
+        .insert('\u{e000}'.into(), HieroBitmapInfo::default());
+    after.bitmap_table.get_mut(&'a'.into()).unwrap().xadvance += 1;
+    // point 'b' at the bitmap of 'd', which has the same size
+    let d = *after.glyph('d').unwrap();
+    let b = after.bitmap_table.get_mut(&'b'.into()).unwrap();
+    assert_eq!((b.width, b.height), (d.width, d.height));
+    b.x = d.x;
+    b.y = d.y;
+    after.kerning_table.insert(('A'.into(), 'V'.into()), -3);
+
+    let diff = AtlasDiff::new(&before, &after).unwrap();
+    assert_eq!(diff.info.len(), 1);
+    assert_eq!(diff.added_glyphs, vec![GlyphId(0xE000)]);
+    assert_eq!(diff.removed_glyphs, vec![GlyphId::from('z')]);
+    assert_eq!(diff.metrics.len(), 1);
+    assert_eq!(diff.metrics[0].glyph, 'a');
+    assert_eq!(diff.pixels.len(), 1);
+    assert_eq!(diff.pixels[0].glyph, 'b');
+    assert_eq!(diff.kerning.len(), 1);
+    assert!(diff.to_string().contains("~ info.size: 32 -> 40"));
+
+    let mut converted = before.clone();
+    converted
+        .convert_pages(ColorSpace::Linear, AlphaMode::Premultiplied)
+        .unwrap();
+    assert!(AtlasDiff::new(&before, &converted).unwrap().is_empty());
+}
@@ -0,0 +1,251 @@
+// cpu drawing helpers shared by the preview and overlay renderers, every target is an 8-bit rgba page
+
+use super::*;
+
+/// 3x5 pixel shapes (msb is the leftmost column) used to label glyphs
+const TINY_FONT: [(char, [u8; 5]); 40] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b111, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b111, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('!', [0b010, 0b010, 0b010, 0b000, 0b010]),
+    ('#', [0b101, 0b111, 0b101, 0b111, 0b101]),
+];
+
+pub const TINY_FONT_HEIGHT: i32 = 5;
+const TINY_FONT_ADVANCE: i32 = 4;
+
+pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> [u8; 4] {
+    [r, g, b, a]
+}
+
+/// blends a straight alpha `color` over the texel at (x, y), texels outside of the page are ignored
+pub fn blend(page: &mut HieroPage, x: i32, y: i32, color: [u8; 4]) {
+    let info = page.info();
+    if x < 0 || y < 0 || x >= info.width as i32 || y >= info.height as i32 {
+        return;
+    }
+    let offset = y as usize * info.line_size as usize + x as usize * 4;
+    let texel = &mut page.pixels_mut()[offset..offset + 4];
+    let alpha = color[3] as u32;
+    for (dst, &src) in texel[..3].iter_mut().zip(color[..3].iter()) {
+        *dst = ((src as u32 * alpha + *dst as u32 * (255 - alpha) + 127) / 255) as u8;
+    }
+    texel[3] = (alpha + (texel[3] as u32 * (255 - alpha) + 127) / 255) as u8;
+}
+
+pub fn fill_rect(page: &mut HieroPage, (x, y): (i32, i32), (w, h): (i32, i32), color: [u8; 4]) {
+    for py in y..y + h {
+        for px in x..x + w {
+            blend(page, px, py, color);
+        }
+    }
+}
+
+/// draws a 1 texel wide outline just inside of the rect
+pub fn stroke_rect(page: &mut HieroPage, (x, y): (i32, i32), (w, h): (i32, i32), color: [u8; 4]) {
+    if w <= 0 || h <= 0 {
+        return;
+    }
+    fill_rect(page, (x, y), (w, 1), color);
+    if h > 1 {
+        fill_rect(page, (x, y + h - 1), (w, 1), color);
+    }
+    fill_rect(page, (x, y + 1), (1, h - 2), color);
+    if w > 1 {
+        fill_rect(page, (x + w - 1, y + 1), (1, h - 2), color);
+    }
+}
+
+pub fn text_width(text: &str) -> i32 {
+    (text.chars().count() as i32 * TINY_FONT_ADVANCE - 1).max(0)
+}
+
+/// draws `text` with the built-in 3x5 font, characters it doesn't know are left blank
+pub fn draw_text(page: &mut HieroPage, (x, y): (i32, i32), text: &str, color: [u8; 4]) {
+    for (index, c) in text.chars().enumerate() {
+        let shape = match TINY_FONT
+            .iter()
+            .find(|(key, _)| *key == c.to_ascii_uppercase())
+        {
+            Some((_, shape)) => shape,
+            None => continue,
+        };
+        let left = x + index as i32 * TINY_FONT_ADVANCE;
+        for (row, bits) in shape.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    blend(page, left + col, y + row as i32, color);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the coverage of a glyph's texel as an rgba color.\
+/// Glyphs packed into a single channel (`chnl` 1, 2, 4 or 8) are turned into white with that channel as alpha.
+/// Channels `common` declares as outline are drawn black, zero/one channels are forced to 0/255.
+pub fn glyph_texel(
+    source: &HieroPage,
+    glyph: &HieroBitmapInfo,
+    common: &HieroCommon,
+    x: u32,
+    y: u32,
+) -> [u8; 4] {
+    let info = source.info();
+    let offset = y as usize * info.line_size as usize + x as usize * 4;
+    let texel = &source.pixels()[offset..offset + 4];
+    // bmfont channel bits are blue = 1, green = 2, red = 4, alpha = 8
+    let channel = match glyph.channel {
+        1 => Some(2),
+        2 => Some(1),
+        4 => Some(0),
+        8 => Some(3),
+        _ => None,
+    };
+    match channel {
+        Some(channel) => coverage(texel[channel], common.channel_content(channel)),
+        None => match common.channel_content(3) {
+            content @ Some(ChannelContent::Outline)
+            | content @ Some(ChannelContent::GlyphAndOutline) => coverage(texel[3], content),
+            _ => {
+                let mut color = rgba(texel[0], texel[1], texel[2], texel[3]);
+                for (index, value) in color.iter_mut().enumerate() {
+                    match common.channel_content(index) {
+                        Some(ChannelContent::Zero) => *value = 0,
+                        Some(ChannelContent::One) => *value = 255,
+                        _ => (),
+                    }
+                }
+                color
+            }
+        },
+    }
+}
+
+/// turns a single channel into a color, glyphs are white and outlines black
+fn coverage(value: u8, content: Option<ChannelContent>) -> [u8; 4] {
+    match content {
+        Some(ChannelContent::Outline) => rgba(0, 0, 0, value),
+        // the outline fades in below 128 and the glyph fades over it above
+        Some(ChannelContent::GlyphAndOutline) if value >= 128 => {
+            let glyph = (value - 128) * 2;
+            rgba(glyph, glyph, glyph, 255)
+        }
+        Some(ChannelContent::GlyphAndOutline) => rgba(0, 0, 0, value * 2),
+        Some(ChannelContent::Zero) => rgba(0, 0, 0, 0),
+        Some(ChannelContent::One) => rgba(255, 255, 255, 255),
+        Some(ChannelContent::Glyph) | None => rgba(255, 255, 255, value),
+    }
+}
+
+/// blends the glyph's rect of `source` (an rgba page) so that its top left corner lands at `dest`
+pub fn draw_glyph(
+    page: &mut HieroPage,
+    source: &HieroPage,
+    glyph: &HieroBitmapInfo,
+    common: &HieroCommon,
+    (dx, dy): (i32, i32),
+) {
+    let info = source.info();
+    for y in 0..glyph.height {
+        for x in 0..glyph.width {
+            let (sx, sy) = (glyph.x + x, glyph.y + y);
+            if sx < 0 || sy < 0 || sx >= info.width as i32 || sy >= info.height as i32 {
+                continue;
+            }
+            let color = glyph_texel(source, glyph, common, sx as u32, sy as u32);
+            blend(page, dx + x, dy + y, color);
+        }
+    }
+}
+
+/// `U+XXXX` style label of a character
+pub fn code_point_label(glyph: GlyphId) -> String {
+    glyph.to_string()
+}
+
+/// the character itself if the built-in font can draw it, its [`code_point_label`] otherwise
+pub fn glyph_label(glyph: GlyphId) -> String {
+    match glyph.as_char() {
+        Some(c) if TINY_FONT.iter().any(|&(key, _)| key == c) => c.to_string(),
+        _ => code_point_label(glyph),
+    }
+}
+
+#[test]
+fn channel_aware_texels() {
+    // red holds 200, the other channels 100
+    let page = HieroPage::blank(1, 1, 4).with_pixels(vec![200, 100, 100, 100]);
+    let packed = HieroBitmapInfo {
+        channel: 4,
+        ..HieroBitmapInfo::default()
+    };
+    let all = HieroBitmapInfo {
+        channel: 15,
+        ..HieroBitmapInfo::default()
+    };
+    let mut common = HieroCommon::default();
+    assert_eq!(
+        glyph_texel(&page, &packed, &common, 0, 0),
+        rgba(255, 255, 255, 200)
+    );
+    assert_eq!(
+        glyph_texel(&page, &all, &common, 0, 0),
+        rgba(200, 100, 100, 100)
+    );
+
+    common.red_chnl = Some(2);
+    assert_eq!(
+        glyph_texel(&page, &packed, &common, 0, 0),
+        rgba(144, 144, 144, 255)
+    );
+    common.red_chnl = Some(1);
+    assert_eq!(
+        glyph_texel(&page, &packed, &common, 0, 0),
+        rgba(0, 0, 0, 200)
+    );
+
+    common.red_chnl = Some(4);
+    common.green_chnl = Some(4);
+    common.blue_chnl = Some(3);
+    common.alpha_chnl = Some(0);
+    assert_eq!(
+        glyph_texel(&page, &all, &common, 0, 0),
+        rgba(255, 255, 0, 100)
+    );
+}
@@ -0,0 +1,168 @@
+use super::*;
+use std::path::Path;
+
+/// The id a glyph is stored under.\
+/// For text fonts it's the unicode code point of the character, icon fonts may use private use code points or
+/// raw glyph indices that aren't characters at all. `char`s convert into it, so most lookups can take either.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GlyphId(pub u32);
+
+impl GlyphId {
+    /// the character of the glyph, `None` if the id isn't a unicode scalar value
+    pub fn as_char(self) -> Option<char> {
+        std::char::from_u32(self.0)
+    }
+
+    /// `'A' (U+0041)` for characters, `#1234` otherwise, used in reports
+    pub fn describe(self) -> String {
+        match self.as_char() {
+            Some(glyph) => format!("{:?} ({})", glyph, self),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl From<char> for GlyphId {
+    fn from(glyph: char) -> Self {
+        Self(glyph as u32)
+    }
+}
+
+impl PartialEq<char> for GlyphId {
+    fn eq(&self, glyph: &char) -> bool {
+        self.0 == *glyph as u32
+    }
+}
+
+impl From<u32> for GlyphId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+/// `U+0041` for characters, `#1234` for ids that aren't one
+impl std::fmt::Display for GlyphId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.as_char() {
+            Some(_) => write!(f, "U+{:04X}", self.0),
+            None => write!(f, "#{}", self.0),
+        }
+    }
+}
+
+/// Symbolic names of the glyphs of an icon font, e.g. `icon_save`.\
+/// Usually loaded from a sidecar file with one `name = id` pair per line, where the id is a code point
+/// (`U+E001`, `0xE001`), a decimal glyph id or the character itself. Blank lines and lines starting with `#`
+/// are skipped, anywhere else `#` is part of the name or id (`sharp = #`).
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct GlyphNames {
+    names: BTreeMap<String, GlyphId>,
+}
+
+impl GlyphNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut names = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, id) = line
+                .split_once('=')
+                .map(|(name, id)| (name.trim(), id.trim()))
+                .filter(|(name, id)| !name.is_empty() && !id.is_empty())
+                .ok_or_else(|| ParseError::new(Some(index + 1), "expected 'name = id'"))?;
+            let id = parse_glyph_id(id)
+                .ok_or_else(|| ParseError::new(Some(index + 1), "glyph id isn't a number"))?;
+            names.insert(name, id);
+        }
+        Ok(names)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(Error::from)
+            .and_then(|text| Self::parse(&text))
+            .map_err(|err| Error::file(path, err))
+    }
+
+    /// names a glyph, returns the glyph the name referred to before
+    pub fn insert<N: Into<String>, G: Into<GlyphId>>(
+        &mut self,
+        name: N,
+        glyph: G,
+    ) -> Option<GlyphId> {
+        self.names.insert(name.into(), glyph.into())
+    }
+
+    pub fn get(&self, name: &str) -> Option<GlyphId> {
+        self.names.get(name).copied()
+    }
+
+    /// drops the names of glyphs `keep` returns false for
+    pub fn retain<F: FnMut(GlyphId) -> bool>(&mut self, mut keep: F) {
+        self.names.retain(|_, id| keep(*id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// names in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, GlyphId)> + '_ {
+        self.names.iter().map(|(name, &id)| (name.as_str(), id))
+    }
+}
+
+fn parse_glyph_id(text: &str) -> Option<GlyphId> {
+    let mut chars = text.chars();
+    let id = match (chars.next(), chars.next()) {
+        (Some(glyph), None) if !glyph.is_ascii_digit() => glyph as u32,
+        _ => super::charset::parse_code_point(text).or_else(|| text.parse().ok())?,
+    };
+    Some(GlyphId(id))
+}
+
+#[test]
+fn glyph_names_file() {
+    let names = GlyphNames::parse(
+        "# material icons\n\
+         icon_save = U+E161\n\
+         icon_open=0xE2C7\n\
+         \n\
+         \t# indented comment\n\
+         glyph_index = 70000\n\
+         heart = ♥\n\
+         c# = U+266F\n\
+         sharp = #\n",
+    )
+    .unwrap();
+    assert_eq!(names.len(), 6);
+    assert_eq!(names.get("c#"), Some(GlyphId(0x266F)));
+    assert_eq!(names.get("sharp"), Some('#'.into()));
+    assert_eq!(names.get("icon_save"), Some(GlyphId(0xE161)));
+    assert_eq!(names.get("icon_open"), Some(GlyphId(0xE2C7)));
+    assert_eq!(names.get("heart"), Some('♥'.into()));
+    assert_eq!(names.get("glyph_index"), Some(GlyphId(70000)));
+    assert_eq!(names.get("missing"), None);
+
+    let err = GlyphNames::parse("ok = 1\nbroken\n").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Parse(ParseError { line: Some(2), .. })
+    ));
+    assert!(GlyphNames::parse("bad = U+XYZ").is_err());
+
+    assert_eq!(GlyphId(0x41).to_string(), "U+0041");
+    assert_eq!(GlyphId(0xD800).to_string(), "#55296");
+    assert_eq!(GlyphId(0x1F600).as_char(), Some('😀'));
+}
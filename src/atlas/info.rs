@@ -1,6 +1,6 @@
 use super::*; 
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct HieroInfo {
     pub face: String,
     pub size: i32,
@@ -13,21 +13,102 @@ pub struct HieroInfo {
     pub aa: i32,
     pub padding: Vec<i32>,
     pub spacing: Vec<i32>,
+    /// outline thickness, bmfont writes it and hiero doesn't
+    pub outline: Option<i32>,
 }
 
 
-#[derive(Serialize, Deserialize, Default, Copy, Clone)]
+/// How the samples of a decoded page are laid out
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum PageColorType {
+    Grayscale,
+    GrayscaleAlpha,
+    Rgb,
+    #[default]
+    Rgba,
+    /// one palette index per pixel, see [`HieroPage::palette`]
+    Indexed,
+}
+
+impl PageColorType {
+    pub fn from_samples(samples: u32) -> Self {
+        match samples {
+            1 => Self::Grayscale,
+            2 => Self::GrayscaleAlpha,
+            3 => Self::Rgb,
+            _ => Self::Rgba,
+        }
+    }
+
+    pub fn samples(&self) -> u32 {
+        match self {
+            Self::Grayscale | Self::Indexed => 1,
+            Self::GrayscaleAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+}
+
+impl From<png::ColorType> for PageColorType {
+    fn from(color_type: png::ColorType) -> Self {
+        match color_type {
+            png::ColorType::Grayscale => Self::Grayscale,
+            png::ColorType::GrayscaleAlpha => Self::GrayscaleAlpha,
+            png::ColorType::RGB => Self::Rgb,
+            png::ColorType::RGBA => Self::Rgba,
+            png::ColorType::Indexed => Self::Indexed,
+        }
+    }
+}
+
+impl From<PageColorType> for png::ColorType {
+    fn from(color_type: PageColorType) -> Self {
+        match color_type {
+            PageColorType::Grayscale => Self::Grayscale,
+            PageColorType::GrayscaleAlpha => Self::GrayscaleAlpha,
+            PageColorType::Rgb => Self::RGB,
+            PageColorType::Rgba => Self::RGBA,
+            PageColorType::Indexed => Self::Indexed,
+        }
+    }
+}
+
+/// Describes the pixels of a decoded page.\
+/// Pages unpacked with the default transformations are always 8-bit and never indexed,
+/// 16-bit samples are stored big endian and sub-byte depths are packed msb first like in png.
+#[derive(Serialize, Deserialize, Copy, Clone)]
 pub struct PageInfo {
     pub width: u32,
     pub height: u32,
     pub samples: u32, // RGB(samples = 3) or RGBA(samples =4)
     pub line_size: u32,
+    pub color_space: ColorSpace,
+    pub alpha_mode: AlphaMode,
+    /// bits per sample (1, 2, 4, 8 or 16)
+    pub bit_depth: u8,
+    pub color_type: PageColorType,
+}
+
+impl Default for PageInfo {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            samples: 4,
+            line_size: 0,
+            color_space: ColorSpace::default(),
+            alpha_mode: AlphaMode::default(),
+            bit_depth: 8,
+            color_type: PageColorType::default(),
+        }
+    }
 }
 
 
 /// This struct is pretty much just a header, for a sub-image inside the page\
 /// To get actual pixel data for the bitmap, just look up the page and read the sub-image at top left (x,y) , borrown-right:(x+width,y+height)
-#[derive(Serialize, Deserialize, Default, Copy, Clone)]
+#[derive(Serialize, Deserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct HieroBitmapInfo {
     pub x: i32,
     pub y: i32,
@@ -0,0 +1,114 @@
+use super::*;
+
+const NO_GLYPH: u32 = u32::MAX;
+
+/// A read-only snapshot of the glyph and kerning tables of an atlas, laid out for hot render loops.\
+/// Latin-1 glyphs are found through a direct index, every other glyph by binary search over sorted ids and
+/// kerning pairs by binary search over packed `(first, second)` keys, no hashing is involved.
+/// Build it once with [`HieroAtlas::lookup`] after the atlas was loaded, it doesn't see later edits to the atlas.
+#[derive(Clone, Debug)]
+pub struct GlyphLookup {
+    /// index into `glyphs` for ids below 256, [`NO_GLYPH`] if absent
+    latin1: Box<[u32; 256]>,
+    ids: Vec<u32>,
+    glyphs: Vec<HieroBitmapInfo>,
+    /// `first << 32 | second`, sorted
+    pairs: Vec<u64>,
+    amounts: Vec<i32>,
+}
+
+impl GlyphLookup {
+    pub fn new(atlas: &HieroAtlas) -> Self {
+        let mut glyphs: Vec<(GlyphId, HieroBitmapInfo)> = atlas
+            .bitmap_table
+            .iter()
+            .map(|(&id, &bitmap)| (id, bitmap))
+            .collect();
+        glyphs.sort_unstable_by_key(|&(id, _)| id);
+        let mut latin1 = Box::new([NO_GLYPH; 256]);
+        for (index, &(id, _)) in glyphs.iter().take_while(|(id, _)| id.0 < 256).enumerate() {
+            latin1[id.0 as usize] = index as u32;
+        }
+
+        let mut kerning: Vec<(u64, i32)> = atlas
+            .kerning_table
+            .iter()
+            .map(|(&(first, second), &amount)| (pair_key(first, second), amount))
+            .collect();
+        kerning.sort_unstable();
+
+        Self {
+            latin1,
+            ids: glyphs.iter().map(|(id, _)| id.0).collect(),
+            glyphs: glyphs.into_iter().map(|(_, bitmap)| bitmap).collect(),
+            pairs: kerning.iter().map(|&(pair, _)| pair).collect(),
+            amounts: kerning.into_iter().map(|(_, amount)| amount).collect(),
+        }
+    }
+
+    /// the glyph of a character or [`GlyphId`], same as [`HieroAtlas::glyph`]
+    #[inline]
+    pub fn glyph<G: Into<GlyphId>>(&self, glyph: G) -> Option<&HieroBitmapInfo> {
+        let id = glyph.into().0;
+        let index = if id < 256 {
+            Some(self.latin1[id as usize]).filter(|&index| index != NO_GLYPH)? as usize
+        } else {
+            self.ids.binary_search(&id).ok()?
+        };
+        Some(&self.glyphs[index])
+    }
+
+    /// the kerning amount between two glyphs, 0 if the pair isn't kerned, same as [`HieroAtlas::kerning`]
+    #[inline]
+    pub fn kerning<G: Into<GlyphId>>(&self, first: G, second: G) -> i32 {
+        if self.pairs.is_empty() {
+            return 0;
+        }
+        self.pairs
+            .binary_search(&pair_key(first.into(), second.into()))
+            .map_or(0, |index| self.amounts[index])
+    }
+
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+}
+
+fn pair_key(first: GlyphId, second: GlyphId) -> u64 {
+    (first.0 as u64) << 32 | second.0 as u64
+}
+
+impl HieroAtlas {
+    /// a [`GlyphLookup`] of the glyphs and kerning pairs as they are now
+    pub fn lookup(&self) -> GlyphLookup {
+        GlyphLookup::new(self)
+    }
+}
+
+#[test]
+fn lookup_matches_tables() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let mut atlas = HieroAtlas::new().with_font_file(font_file).build().unwrap();
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0x4F60), HieroBitmapInfo::default());
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0xD800), HieroBitmapInfo::default());
+    atlas.kerning_table.insert(('A'.into(), 'V'.into()), -2);
+    atlas.kerning_table.insert((GlyphId(0x4F60), 'A'.into()), 3);
+
+    let lookup = atlas.lookup();
+    assert_eq!(lookup.len(), atlas.bitmap_table.len());
+    for id in (0..0x1_0000).map(GlyphId) {
+        assert_eq!(lookup.glyph(id), atlas.glyph(id), "{}", id);
+    }
+    assert_eq!(lookup.kerning('A', 'V'), -2);
+    assert_eq!(lookup.kerning('V', 'A'), 0);
+    assert_eq!(lookup.kerning(GlyphId(0x4F60), 'A'.into()), 3);
+    assert!(HieroAtlas::default().lookup().glyph('A').is_none());
+}
@@ -0,0 +1,395 @@
+use super::*;
+use std::path::{Path, PathBuf};
+
+/// Text formats the metadata of an atlas can be written in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MetadataFormat {
+    Json,
+    /// needs the `ron` feature
+    Ron,
+    /// needs the `toml` feature
+    Toml,
+}
+
+impl MetadataFormat {
+    pub const ALL: [MetadataFormat; 3] = [Self::Json, Self::Ron, Self::Toml];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Ron => "ron",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// false if the crate was built without the feature this format needs
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::Json => true,
+            Self::Ron => cfg!(feature = "ron"),
+            Self::Toml => cfg!(feature = "toml"),
+        }
+    }
+
+    /// guesses the format from the extension of `path`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.extension() == extension)
+    }
+}
+
+impl std::str::FromStr for MetadataFormat {
+    type Err = Error;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.extension() == name)
+            .ok_or_else(|| Error::UnknownMetadataFormat(name.to_string()))
+    }
+}
+
+/// A glyph keyed by its [`GlyphId`], laid out like a `char` line of a .fnt file
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GlyphMetadata {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub xoffset: i32,
+    pub yoffset: i32,
+    pub xadvance: i32,
+    pub page: i32,
+    pub channel: i32,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KerningMetadata {
+    pub first: u32,
+    pub second: u32,
+    pub amount: i32,
+}
+
+/// A page of the atlas, `file` and `mips` are png files relative to the metadata file.\
+/// The pngs hold the texels as they are stored, `encoding`, `color_space` and `alpha_mode` are what
+/// the page (and its mips) get back on import.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PageMetadata {
+    pub file: String,
+    pub encoding: PageEncoding,
+    pub color_space: ColorSpace,
+    pub alpha_mode: AlphaMode,
+    /// mip levels starting at half size, see [`HieroAtlas::mip_pages`]
+    #[serde(default)]
+    pub mips: Vec<String>,
+}
+
+impl PageMetadata {
+    fn new(file: String, page: &CompressedPage, mips: Vec<String>) -> Self {
+        Self {
+            file,
+            encoding: page.encoding,
+            color_space: page.info.color_space,
+            alpha_mode: page.info.alpha_mode,
+            mips,
+        }
+    }
+
+    /// stores the png `data` the way this page was stored before it was exported
+    fn restore(&self, data: Vec<u8>) -> Result<CompressedPage, Error> {
+        match self.encoding {
+            PageEncoding::Png => {
+                let mut page = CompressedPage::png(data);
+                page.info.color_space = self.color_space;
+                page.info.alpha_mode = self.alpha_mode;
+                Ok(page)
+            }
+            encoding => CompressedPage::encode(
+                &HieroPage::decode_png(&data)?.with_conventions(self.color_space, self.alpha_mode),
+                encoding,
+            ),
+        }
+    }
+}
+
+/// Everything but the pixels of an atlas in a shape json, ron and toml can all represent.\
+/// Glyphs and kerning pairs are sorted lists instead of maps, `pages` names the page images and
+/// records how each page was stored.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AtlasMetadata {
+    pub info: HieroInfo,
+    pub common: HieroCommon,
+    pub pages: Vec<PageMetadata>,
+    pub glyphs: Vec<GlyphMetadata>,
+    pub kerning: Vec<KerningMetadata>,
+    /// symbolic glyph names, see [`HieroAtlas::glyph_names`]
+    #[serde(default)]
+    pub names: BTreeMap<String, u32>,
+    /// gpu textures exported from the pages, their paths stay relative to the directory they were
+    /// exported to
+    #[serde(default)]
+    pub textures: Vec<TextureRef>,
+}
+
+impl AtlasMetadata {
+    pub fn new(atlas: &HieroAtlas, pages: Vec<PageMetadata>) -> Self {
+        let mut glyphs: Vec<GlyphMetadata> = atlas
+            .bitmap_table
+            .iter()
+            .map(|(&id, bitmap)| GlyphMetadata {
+                id: id.0,
+                x: bitmap.x,
+                y: bitmap.y,
+                width: bitmap.width,
+                height: bitmap.height,
+                xoffset: bitmap.xoffset,
+                yoffset: bitmap.yoffset,
+                xadvance: bitmap.xadvance,
+                page: bitmap.page,
+                channel: bitmap.channel,
+            })
+            .collect();
+        glyphs.sort_by_key(|glyph| glyph.id);
+
+        let mut kerning: Vec<KerningMetadata> = atlas
+            .kerning_table
+            .iter()
+            .map(|(&(first, second), &amount)| KerningMetadata {
+                first: first.0,
+                second: second.0,
+                amount,
+            })
+            .collect();
+        kerning.sort_by_key(|pair| (pair.first, pair.second));
+
+        Self {
+            info: atlas.info.clone(),
+            common: atlas.common.clone(),
+            pages,
+            glyphs,
+            kerning,
+            names: atlas
+                .glyph_names
+                .iter()
+                .map(|(name, id)| (name.to_string(), id.0))
+                .collect(),
+            textures: atlas.textures.clone(),
+        }
+    }
+
+    pub fn to_string(&self, format: MetadataFormat) -> Result<String, Error> {
+        match format {
+            MetadataFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|err| Error::serialization("json", err))
+            }
+            #[cfg(feature = "ron")]
+            MetadataFormat::Ron => ron::ser::to_string_pretty(self, Default::default())
+                .map_err(|err| Error::serialization("ron", err)),
+            #[cfg(feature = "toml")]
+            MetadataFormat::Toml => {
+                toml::to_string_pretty(self).map_err(|err| Error::serialization("toml", err))
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => Err(Error::UnsupportedMetadataFormat(unsupported)),
+        }
+    }
+
+    pub fn from_str(text: &str, format: MetadataFormat) -> Result<Self, Error> {
+        match format {
+            MetadataFormat::Json => {
+                serde_json::from_str(text).map_err(|err| Error::serialization("json", err))
+            }
+            #[cfg(feature = "ron")]
+            MetadataFormat::Ron => {
+                ron::from_str(text).map_err(|err| Error::serialization("ron", err))
+            }
+            #[cfg(feature = "toml")]
+            MetadataFormat::Toml => {
+                toml::from_str(text).map_err(|err| Error::serialization("toml", err))
+            }
+            #[allow(unreachable_patterns)]
+            unsupported => Err(Error::UnsupportedMetadataFormat(unsupported)),
+        }
+    }
+
+    /// Turns the metadata back into an atlas, `read_file` is handed the file names of the pages and
+    /// mips listed in [`AtlasMetadata::pages`] and returns their png bytes.
+    pub fn into_atlas<F>(self, mut read_file: F) -> Result<HieroAtlas, Error>
+    where
+        F: FnMut(&str) -> Result<Vec<u8>, Error>,
+    {
+        let mut atlas = HieroAtlas {
+            info: self.info,
+            common: self.common,
+            textures: self.textures,
+            ..HieroAtlas::default()
+        };
+        for page in &self.pages {
+            atlas
+                .compressed_pages
+                .push(page.restore(read_file(&page.file)?)?);
+            let mips = page
+                .mips
+                .iter()
+                .map(|mip| page.restore(read_file(mip)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            if !mips.is_empty() {
+                atlas
+                    .mip_pages
+                    .resize_with(atlas.compressed_pages.len() - 1, Vec::new);
+                atlas.mip_pages.push(mips);
+            }
+        }
+        for glyph in self.glyphs {
+            atlas.bitmap_table.insert(
+                GlyphId(glyph.id),
+                HieroBitmapInfo {
+                    x: glyph.x,
+                    y: glyph.y,
+                    width: glyph.width,
+                    height: glyph.height,
+                    xoffset: glyph.xoffset,
+                    yoffset: glyph.yoffset,
+                    xadvance: glyph.xadvance,
+                    page: glyph.page,
+                    channel: glyph.channel,
+                },
+            );
+        }
+        for pair in self.kerning {
+            atlas
+                .kerning_table
+                .insert((GlyphId(pair.first), GlyphId(pair.second)), pair.amount);
+        }
+        for (name, id) in self.names {
+            atlas.glyph_names.insert(name, GlyphId(id));
+        }
+        Ok(atlas)
+    }
+}
+
+impl HieroAtlas {
+    /// Writes `{name}.{ext}` to `dir` along with one `{name}_page{i}.png` per page and one
+    /// `{name}_page{i}_mip{level}.png` per mip level, png pages are copied verbatim and pages in other
+    /// encodings are converted to png.\
+    /// Returns the path of the metadata file.
+    pub fn export_metadata<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        name: &str,
+        format: MetadataFormat,
+    ) -> Result<PathBuf, Error> {
+        let dir = dir.as_ref();
+        let mut pages = Vec::with_capacity(self.compressed_pages.len());
+        for (page_index, page) in self.compressed_pages.iter().enumerate() {
+            let page_name = format!("{}_page{}.png", name, page_index);
+            std::fs::write(dir.join(&page_name), page.to_png()?)?;
+            let mut mip_names = Vec::new();
+            for (level, mip) in self
+                .mip_pages
+                .get(page_index)
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                let mip_name = format!("{}_page{}_mip{}.png", name, page_index, level + 1);
+                std::fs::write(dir.join(&mip_name), mip.to_png()?)?;
+                mip_names.push(mip_name);
+            }
+            pages.push(PageMetadata::new(page_name, page, mip_names));
+        }
+        let text = AtlasMetadata::new(self, pages).to_string(format)?;
+        let path = dir.join(format!("{}.{}", name, format.extension()));
+        std::fs::write(&path, text)?;
+        Ok(path)
+    }
+
+    /// Reads a metadata file written by [`HieroAtlas::export_metadata`] along with the pages it names,
+    /// the format is picked from the extension of `path`.
+    pub fn import_metadata<P: AsRef<Path>>(path: P) -> Result<HieroAtlas, Error> {
+        let path = path.as_ref();
+        let format = MetadataFormat::from_path(path)
+            .ok_or_else(|| Error::UnknownMetadataExtension(path.to_path_buf()))?;
+        let text = std::fs::read_to_string(path).map_err(|err| Error::file(path, err))?;
+        let metadata =
+            AtlasMetadata::from_str(&text, format).map_err(|err| Error::file(path, err))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        metadata.into_atlas(|file| {
+            let file = dir.join(file);
+            std::fs::read(&file).map_err(|err| Error::file(file, err))
+        })
+    }
+}
+
+#[test]
+fn metadata_round_trip() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let mut atlas = HieroAtlas::new()
+        .with_font_file(font_file)
+        .with_pages(vec![page_file.clone()])
+        .build()
+        .unwrap();
+    atlas.kerning_table.insert(('A'.into(), 'V'.into()), -2);
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0xD800), HieroBitmapInfo::default());
+    atlas.glyph_names.insert("icon", GlyphId(0xD800));
+    atlas.info.outline = Some(0);
+    atlas.common.alpha_chnl = Some(2);
+
+    let dir = std::env::temp_dir().join(format!("hiero_pack_metadata_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for &format in MetadataFormat::ALL.iter().filter(|f| f.is_supported()) {
+        let path = atlas.export_metadata(&dir, "uroob", format).unwrap();
+        let imported = HieroAtlas::import_metadata(&path).unwrap();
+        assert!(
+            AtlasDiff::new(&atlas, &imported).unwrap().is_empty(),
+            "{:?}",
+            format
+        );
+        assert_eq!(imported.compressed_pages[0].data, page_file);
+        assert_eq!(imported.glyph_names, atlas.glyph_names);
+    }
+
+    // conventions, encodings, mips and texture references survive the png detour
+    atlas
+        .convert_pages(ColorSpace::Linear, AlphaMode::Premultiplied)
+        .unwrap();
+    atlas.reencode_pages(PageEncoding::Raw).unwrap();
+    atlas
+        .generate_mips(&GlyphMipOptions {
+            max_levels: Some(3),
+        })
+        .unwrap();
+    atlas.textures.push(TextureRef {
+        container: TextureContainer::Ktx2,
+        path: "uroob.ktx2".to_string(),
+        pages: vec![0],
+        mip_levels: 3,
+        color_space: ColorSpace::Linear,
+        alpha_mode: AlphaMode::Premultiplied,
+    });
+    for &format in MetadataFormat::ALL.iter().filter(|f| f.is_supported()) {
+        let path = atlas.export_metadata(&dir, "uroob", format).unwrap();
+        let imported = HieroAtlas::import_metadata(&path).unwrap();
+        let (page, imported_page) = (&atlas.compressed_pages[0], &imported.compressed_pages[0]);
+        assert_eq!(imported_page.encoding, PageEncoding::Raw);
+        assert_eq!(imported_page.info.color_space, ColorSpace::Linear);
+        assert_eq!(imported_page.info.alpha_mode, AlphaMode::Premultiplied);
+        assert_eq!(imported_page.data, page.data);
+        assert_eq!(imported.mip_pages.len(), 1);
+        assert_eq!(imported.mip_pages[0].len(), 2);
+        for (mip, imported_mip) in atlas.mip_pages[0].iter().zip(&imported.mip_pages[0]) {
+            assert_eq!(imported_mip.encoding, PageEncoding::Raw);
+            assert_eq!(imported_mip.info.alpha_mode, AlphaMode::Premultiplied);
+            assert_eq!(imported_mip.data, mip.data);
+        }
+        assert_eq!(imported.textures, atlas.textures);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,396 @@
+use super::*;
+
+impl HieroPage {
+    /// returns a copy with 4 samples per pixel, gray is broadcast and missing alpha is opaque
+    pub fn to_rgba(&self) -> HieroPage {
+        let info = self.info();
+        let samples = info.samples as usize;
+        if samples == 4 {
+            return self.clone();
+        }
+        let pixels = self
+            .pixels()
+            .chunks(samples)
+            .flat_map(|px| match samples {
+                1 => [px[0], px[0], px[0], 255],
+                2 => [px[0], px[0], px[0], px[1]],
+                _ => [px[0], px[1], px[2], 255],
+            })
+            .collect();
+        HieroPage::blank(info.width, info.height, 4)
+            .with_pixels(pixels)
+            .with_conventions(info.color_space, info.alpha_mode)
+    }
+
+    /// halves the page with a 2x2 box filter in linear light with premultiplied alpha, odd edges are clamped
+    pub fn downsample(&self) -> HieroPage {
+        let info = self.info();
+        let samples = info.samples as usize;
+        let (width, height) = ((info.width / 2).max(1), (info.height / 2).max(1));
+        let mut half = HieroPage::blank(width, height, info.samples)
+            .with_conventions(info.color_space, info.alpha_mode);
+        let src = self.pixels();
+        let src_line = info.line_size as usize;
+        let dst_line = half.info().line_size as usize;
+        let dst = half.pixels_mut();
+        let mut filter = TexelFilter::new(&info);
+        for y in 0..height as usize {
+            let (y0, y1) = (
+                (2 * y).min(info.height as usize - 1),
+                (2 * y + 1).min(info.height as usize - 1),
+            );
+            for x in 0..width as usize {
+                let (x0, x1) = (
+                    (2 * x).min(info.width as usize - 1),
+                    (2 * x + 1).min(info.width as usize - 1),
+                );
+                filter.clear();
+                for &(sx, sy) in [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].iter() {
+                    filter.add(&src[sy * src_line + sx * samples..][..samples]);
+                }
+                filter.resolve(4.0, &mut dst[y * dst_line + x * samples..][..samples]);
+            }
+        }
+        half
+    }
+
+    /// returns the full mip chain, starting with a copy of this page and ending at 1x1
+    pub fn mip_chain(&self) -> Vec<HieroPage> {
+        let mut levels = vec![self.clone()];
+        loop {
+            let last = levels.last().unwrap().info();
+            if last.width <= 1 && last.height <= 1 {
+                break levels;
+            }
+            let next = levels.last().unwrap().downsample();
+            levels.push(next);
+        }
+    }
+}
+
+/// Knobs for the glyph aware mip chain builder
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GlyphMipOptions {
+    /// total number of levels (base included), defaults to the full chain down to 1x1.\
+    /// [`HieroAtlas::glyph_safe_mip_levels`] tells how many levels keep neighbouring glyphs in separate texels.
+    pub max_levels: Option<u32>,
+}
+
+impl HieroAtlas {
+    /// Number of levels (base included) that can be built before glyph ink starts landing in texels
+    /// shared with a neighbour, judged by the gutter `HieroInfo::padding` and `HieroInfo::spacing` leave around the ink.
+    pub fn glyph_safe_mip_levels(&self) -> u32 {
+        let padding = self.info.padding.iter().copied().min().unwrap_or(0).max(0);
+        let spacing = self.info.spacing.iter().copied().min().unwrap_or(0).max(0);
+        let gutter = (2 * padding + spacing).max(1) as u32;
+        // level n merges 2^n texels, which is fine as long as the gutter covers it
+        1 + (31 - gutter.leading_zeros())
+    }
+
+    /// Builds the mip chain of a page from the texels its glyphs own.\
+    /// Only texels inside of a glyph rect are filtered, so whatever lies between the rects never bleeds into
+    /// a glyph, and the averaging happens in linear light with premultiplied alpha. The returned chain starts
+    /// with the base level.
+    pub fn build_glyph_mips(
+        &self,
+        page_index: usize,
+        options: &GlyphMipOptions,
+    ) -> Result<Vec<HieroPage>, Error> {
+        let base = self.try_unpack_page(page_index)?;
+        let info = base.info();
+        let max_levels = options
+            .max_levels
+            .unwrap_or(u32::MAX)
+            .min(mip_level_count(info.width, info.height))
+            .max(1);
+
+        let glyphs: Vec<&HieroBitmapInfo> = self
+            .bitmap_table
+            .values()
+            .filter(|glyph| {
+                glyph.page as usize == page_index && glyph.width > 0 && glyph.height > 0
+            })
+            .collect();
+
+        let mut levels = vec![base];
+        for level in 1..max_levels {
+            let next = downsample_glyphs(&levels[0], &glyphs, level);
+            levels.push(next);
+        }
+        Ok(levels)
+    }
+
+    /// rebuilds [`HieroAtlas::mip_pages`] with as many levels as before after the pages changed, no-op without mips
+    pub fn regenerate_mips(&mut self) -> Result<(), Error> {
+        match self.mip_pages.iter().map(|mips| mips.len()).max() {
+            Some(levels) if levels > 0 => self.generate_mips(&GlyphMipOptions {
+                max_levels: Some(levels as u32 + 1),
+            }),
+            _ => {
+                self.mip_pages.clear();
+                Ok(())
+            }
+        }
+    }
+
+    /// builds glyph aware mips for every page and stores them in [`HieroAtlas::mip_pages`]
+    pub fn generate_mips(&mut self, options: &GlyphMipOptions) -> Result<(), Error> {
+        let mut mip_pages = Vec::with_capacity(self.compressed_pages.len());
+        for (page_index, page) in self.compressed_pages.iter().enumerate() {
+            let levels = self.build_glyph_mips(page_index, options)?;
+            let encoded = levels[1..]
+                .iter()
+                .map(|level| CompressedPage::encode(level, page.encoding))
+                .collect::<Result<Vec<_>, _>>()?;
+            mip_pages.push(encoded);
+        }
+        self.mip_pages = mip_pages;
+        Ok(())
+    }
+
+    /// decodes a page along with its stored mip levels, base level first
+    pub fn try_unpack_mips(&self, page_index: usize) -> Result<Vec<HieroPage>, Error> {
+        let mut levels = vec![self.try_unpack_page(page_index)?];
+        if let Some(mips) = self.mip_pages.get(page_index) {
+            for level in mips {
+                levels.push(level.decode()?);
+            }
+        }
+        Ok(levels)
+    }
+}
+
+/// Box filters `base` by `2^level`, only source texels inside of a glyph rect are averaged and
+/// everything else counts as empty. Glyphs packed edge to edge keep their ink, a texel straddling two
+/// of them averages the ink each rect holds.
+fn downsample_glyphs(base: &HieroPage, glyphs: &[&HieroBitmapInfo], level: u32) -> HieroPage {
+    let info = base.info();
+    let factor = 1usize << level;
+    let samples = info.samples as usize;
+    let mut out = HieroPage::blank(
+        (info.width >> level).max(1),
+        (info.height >> level).max(1),
+        info.samples,
+    )
+    .with_conventions(info.color_space, info.alpha_mode);
+    let out_info = out.info();
+    let out_width = out_info.width as usize;
+
+    let (width, height) = (info.width as usize, info.height as usize);
+
+    // which source texels belong to a glyph, and which output texels any glyph reaches
+    let mut inside = vec![false; width * height];
+    let mut touched = vec![false; out_width * out_info.height as usize];
+    for glyph in glyphs {
+        let clamp = |v: i32, max: usize| (v.max(0) as usize).min(max);
+        let (x0, y0) = (clamp(glyph.x, width), clamp(glyph.y, height));
+        let (x1, y1) = (
+            clamp(glyph.x + glyph.width, width),
+            clamp(glyph.y + glyph.height, height),
+        );
+        for y in y0..y1 {
+            inside[y * width + x0..y * width + x1].fill(true);
+        }
+        for dy in y0 / factor..y1.div_ceil(factor).min(out_info.height as usize) {
+            touched
+                [dy * out_width + x0 / factor..dy * out_width + x1.div_ceil(factor).min(out_width)]
+                .fill(true);
+        }
+    }
+
+    let (src, src_line) = (base.pixels(), info.line_size as usize);
+    let dst_line = out_info.line_size as usize;
+    let dst = out.pixels_mut();
+    let area = (factor * factor) as f32;
+    let mut filter = TexelFilter::new(&info);
+    for (index, _) in touched.iter().enumerate().filter(|(_, &touched)| touched) {
+        let (dx, dy) = (index % out_width, index / out_width);
+        filter.clear();
+        for sy in dy * factor..((dy + 1) * factor).min(height) {
+            for sx in dx * factor..((dx + 1) * factor).min(width) {
+                if inside[sy * width + sx] {
+                    filter.add(&src[sy * src_line + sx * samples..][..samples]);
+                }
+            }
+        }
+        filter.resolve(area, &mut dst[dy * dst_line + dx * samples..][..samples]);
+    }
+    out
+}
+
+/// Averages texels in linear light with premultiplied alpha, so srgb edges don't darken and the color of
+/// transparent texels doesn't bleed into visible ones. The result is stored in the page's own conventions.
+struct TexelFilter {
+    samples: usize,
+    has_alpha: bool,
+    srgb: bool,
+    premultiplied: bool,
+    /// linear premultiplied color sums, alpha last
+    sums: [f32; 4],
+}
+
+impl TexelFilter {
+    fn new(info: &PageInfo) -> Self {
+        let samples = info.samples as usize;
+        Self {
+            samples,
+            has_alpha: samples == 2 || samples == 4,
+            srgb: info.color_space == ColorSpace::Srgb,
+            premultiplied: info.alpha_mode == AlphaMode::Premultiplied,
+            sums: [0.0; 4],
+        }
+    }
+
+    fn color_samples(&self) -> usize {
+        if self.has_alpha {
+            self.samples - 1
+        } else {
+            self.samples
+        }
+    }
+
+    fn clear(&mut self) {
+        self.sums = [0.0; 4];
+    }
+
+    fn add(&mut self, px: &[u8]) {
+        let alpha = if self.has_alpha {
+            px[self.samples - 1] as f32 / 255.0
+        } else {
+            1.0
+        };
+        let color_samples = self.color_samples();
+        for (sum, &value) in self.sums.iter_mut().zip(&px[..color_samples]) {
+            let mut c = value as f32 / 255.0;
+            if self.premultiplied {
+                c = if alpha > 0.0 {
+                    (c / alpha).min(1.0)
+                } else {
+                    0.0
+                };
+            }
+            if self.srgb {
+                c = srgb_to_linear(c);
+            }
+            *sum += c * alpha;
+        }
+        self.sums[3] += alpha;
+    }
+
+    /// writes the average over `area` texels, texels that weren't added count as empty
+    fn resolve(&self, area: f32, texel: &mut [u8]) {
+        let alpha = self.sums[3] / area;
+        let color_samples = self.color_samples();
+        for (value, &sum) in texel[..color_samples].iter_mut().zip(self.sums.iter()) {
+            let mut c = sum / area;
+            if self.has_alpha {
+                c = if alpha > 0.0 {
+                    (c / alpha).min(1.0)
+                } else {
+                    0.0
+                };
+            }
+            if self.srgb {
+                c = linear_to_srgb(c);
+            }
+            if self.premultiplied {
+                // premultiply the 8-bit straight value, like `HieroPage::convert` does
+                c = (c * 255.0).round() / 255.0 * alpha;
+            }
+            *value = (c * 255.0).round() as u8;
+        }
+        if self.has_alpha {
+            texel[color_samples] = (alpha * 255.0).round() as u8;
+        }
+    }
+}
+
+/// number of levels in a full mip chain of a `width`x`height` image
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+#[test]
+fn mip_chain_sizes() {
+    let page = HieroPage::blank(8, 3, 4);
+    let sizes: Vec<_> = page
+        .mip_chain()
+        .iter()
+        .map(|level| (level.info().width, level.info().height))
+        .collect();
+    assert_eq!(sizes, vec![(8, 3), (4, 1), (2, 1), (1, 1)]);
+    assert_eq!(mip_level_count(8, 3), 4);
+}
+
+#[test]
+fn glyph_mips_dont_bleed() {
+    let mut page = HieroPage::blank(8, 2, 1);
+    // a solid 3 texel wide glyph right next to an empty one, and stray ink no glyph owns
+    for y in 0..2 {
+        for x in [0, 1, 2, 6, 7].iter() {
+            page.pixels_mut()[y * 8 + x] = 255;
+        }
+    }
+    let glyph = |x, width| HieroBitmapInfo {
+        x,
+        y: 0,
+        width,
+        height: 2,
+        ..HieroBitmapInfo::default()
+    };
+    let mut atlas = HieroAtlas::default();
+    atlas.bitmap_table.insert('a'.into(), glyph(0, 3));
+    atlas.bitmap_table.insert('b'.into(), glyph(3, 2));
+    atlas.compressed_pages = vec![CompressedPage::encode(&page, PageEncoding::Raw).unwrap()];
+
+    let levels = atlas
+        .build_glyph_mips(
+            0,
+            &GlyphMipOptions {
+                max_levels: Some(2),
+            },
+        )
+        .unwrap();
+    // half of the second texel is 'a', which is half the light of white in srgb
+    assert_eq!(page.downsample().pixels()[..], [255, 188, 0, 255]);
+    // 'a' keeps its last column, the stray ink is dropped
+    assert_eq!(levels[1].pixels()[..], [255, 188, 0, 0]);
+
+    // the color of a transparent texel doesn't tint its opaque neighbour
+    let page = HieroPage::blank(2, 1, 4).with_pixels(vec![255, 0, 0, 255, 0, 255, 0, 0]);
+    assert_eq!(page.downsample().pixels()[..], [255, 0, 0, 128]);
+    let premultiplied = page.convert(&UnpackOptions {
+        alpha_mode: Some(AlphaMode::Premultiplied),
+        ..UnpackOptions::default()
+    });
+    assert_eq!(premultiplied.downsample().pixels()[..], [128, 0, 0, 128]);
+}
+
+#[test]
+fn default_mip_chain() {
+    // hiero's usual gutter, glyphs one texel apart already share a texel at the first mip level
+    let mut atlas = HieroAtlas::default();
+    atlas.info.padding = vec![0, 0, 0, 0];
+    atlas.info.spacing = vec![1, 1];
+    atlas.bitmap_table.insert(
+        'a'.into(),
+        HieroBitmapInfo {
+            width: 3,
+            height: 3,
+            ..HieroBitmapInfo::default()
+        },
+    );
+    let page = HieroPage::blank(8, 8, 1).with_pixels(vec![255; 64]);
+    atlas.compressed_pages = vec![CompressedPage::encode(&page, PageEncoding::Raw).unwrap()];
+    assert_eq!(atlas.glyph_safe_mip_levels(), 1);
+
+    // the chain still goes down to 1x1 unless it's capped
+    atlas.generate_mips(&GlyphMipOptions::default()).unwrap();
+    assert_eq!(atlas.mip_pages[0].len(), 3);
+    atlas
+        .generate_mips(&GlyphMipOptions {
+            max_levels: Some(2),
+        })
+        .unwrap();
+    assert_eq!(atlas.mip_pages[0].len(), 1);
+}
@@ -0,0 +1,333 @@
+use super::*;
+
+const FILTERS: [png::FilterType; 5] = [
+    png::FilterType::NoFilter,
+    png::FilterType::Sub,
+    png::FilterType::Up,
+    png::FilterType::Avg,
+    png::FilterType::Paeth,
+];
+
+/// Knobs for [`HieroAtlas::optimize_pages`]
+#[derive(Copy, Clone, Debug)]
+pub struct PngOptimizeOptions {
+    /// try an indexed encoding when a page has 256 colors or less.\
+    /// Decoding expands the palette again, so unpacked pages keep their sample count
+    pub palette: bool,
+    /// store pages whose color channels are all equal as grayscale.\
+    /// Unpacked pages will then have 1 (or 2 with alpha) samples instead of 3 (or 4)
+    pub grayscale: bool,
+}
+
+impl Default for PngOptimizeOptions {
+    fn default() -> Self {
+        Self {
+            palette: true,
+            grayscale: false,
+        }
+    }
+}
+
+/// Size of a page before and after optimization
+#[derive(Copy, Clone, Debug)]
+pub struct PageSavings {
+    pub page: usize,
+    pub original: usize,
+    pub optimized: usize,
+}
+
+impl std::fmt::Display for PageSavings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let saved = self.original as f64 - self.optimized as f64;
+        let percent = if self.original == 0 {
+            0.0
+        } else {
+            saved * 100.0 / self.original as f64
+        };
+        write!(
+            f,
+            "page {}: {} -> {} bytes ({:.1}% smaller)",
+            self.page, self.original, self.optimized, percent
+        )
+    }
+}
+
+impl HieroAtlas {
+    /// Re-encodes every png page, keeping whichever lossless encoding is the smallest.\
+    /// Ancillary chunks are dropped in the process, pages stored in other encodings are left alone.
+    /// Stored mips are rebuilt from the optimized pages.
+    pub fn optimize_pages(
+        &mut self,
+        options: &PngOptimizeOptions,
+    ) -> Result<Vec<PageSavings>, Error> {
+        let mut savings = Vec::with_capacity(self.compressed_pages.len());
+        for (page, compressed) in self.compressed_pages.iter_mut().enumerate() {
+            let original = compressed.data.len();
+            if compressed.encoding == PageEncoding::Png {
+                if let Some(optimized) = optimize_png(&compressed.data, options)? {
+                    compressed.data = optimized;
+                }
+            }
+            savings.push(PageSavings {
+                page,
+                original,
+                optimized: compressed.data.len(),
+            });
+        }
+        self.regenerate_mips()?;
+        Ok(savings)
+    }
+
+    /// re-encodes every page with `encoding` and rebuilds the stored mips to match
+    pub fn reencode_pages(&mut self, encoding: PageEncoding) -> Result<(), Error> {
+        for page in self.compressed_pages.iter_mut() {
+            *page = page.reencode(encoding)?;
+        }
+        self.regenerate_mips()
+    }
+}
+
+/// returns a smaller encoding of `data`, or `None` if the original is already the smallest
+pub fn optimize_png(data: &[u8], options: &PngOptimizeOptions) -> Result<Option<Vec<u8>>, Error> {
+    // the decoder strips 16-bit samples so re-encoding those wouldn't be lossless
+    let mut header_decoder = png::Decoder::new(data);
+    header_decoder.set_transformations(png::Transformations::IDENTITY);
+    let (header, _) = header_decoder
+        .read_info()
+        .map_err(PageUnpackError::PageDecodeError)?;
+    if header.bit_depth == png::BitDepth::Sixteen {
+        return Ok(None);
+    }
+
+    let page = HieroPage::decode_png(data)?;
+    let gray_page = if options.grayscale {
+        to_grayscale(&page)
+    } else {
+        None
+    };
+
+    let mut best: Option<Vec<u8>> = None;
+    let mut keep_smallest = |candidate: Vec<u8>| {
+        if candidate.len() < best.as_ref().map_or(data.len(), |best| best.len()) {
+            best = Some(candidate);
+        }
+    };
+
+    for candidate in std::iter::once(&page).chain(gray_page.as_ref()) {
+        let color = candidate.info().color_type.into();
+        for &filter in FILTERS.iter() {
+            keep_smallest(encode(
+                candidate,
+                color,
+                png::BitDepth::Eight,
+                None,
+                filter,
+            )?);
+        }
+    }
+
+    if options.palette {
+        if let Some(indexed) = IndexedPage::new(&page) {
+            for &filter in FILTERS.iter() {
+                keep_smallest(indexed.encode(&page, filter)?);
+            }
+        }
+    }
+
+    Ok(best)
+}
+
+fn encode(
+    page: &HieroPage,
+    color: png::ColorType,
+    depth: png::BitDepth,
+    palette: Option<(Vec<u8>, Option<Vec<u8>>)>,
+    filter: png::FilterType,
+) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, page.info().width, page.info().height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        encoder.set_compression(png::Compression::Best);
+        encoder.set_filter(filter);
+        if let Some((palette, trns)) = palette {
+            encoder.set_palette(palette);
+            if let Some(trns) = trns {
+                encoder.set_trns(trns);
+            }
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(page.pixels())?;
+    }
+    Ok(bytes)
+}
+
+/// returns a gray(+alpha) copy of the page if every pixel has equal color channels
+fn to_grayscale(page: &HieroPage) -> Option<HieroPage> {
+    let info = page.info();
+    if info.samples < 3 {
+        return None;
+    }
+    let samples = info.samples as usize;
+    let is_gray = page
+        .pixels()
+        .chunks(samples)
+        .all(|px| px[0] == px[1] && px[1] == px[2]);
+    if !is_gray {
+        return None;
+    }
+    let gray_samples = samples - 2;
+    let pixels = page
+        .pixels()
+        .chunks(samples)
+        .flat_map(|px| {
+            let alpha = if samples == 4 { Some(px[3]) } else { None };
+            std::iter::once(px[0]).chain(alpha)
+        })
+        .collect();
+    Some(HieroPage::blank(info.width, info.height, gray_samples as u32).with_pixels(pixels))
+}
+
+/// a page that has been reduced to a palette
+struct IndexedPage {
+    palette: Vec<u8>,
+    trns: Option<Vec<u8>>,
+    depth: png::BitDepth,
+    indices: Vec<u8>,
+}
+
+impl IndexedPage {
+    /// only rgb(a) pages qualify, an expanded palette would turn gray pages into rgb
+    fn new(page: &HieroPage) -> Option<Self> {
+        let info = page.info();
+        let samples = info.samples as usize;
+        if samples < 3 {
+            return None;
+        }
+        let to_rgba = |px: &[u8]| -> [u8; 4] {
+            match samples {
+                3 => [px[0], px[1], px[2], 255],
+                _ => [px[0], px[1], px[2], px[3]],
+            }
+        };
+
+        let mut colors: HashMap<[u8; 4], u8> = HashMap::new();
+        let mut palette_rgba: Vec<[u8; 4]> = Vec::new();
+        let mut raw_indices = Vec::with_capacity((info.width * info.height) as usize);
+        for px in page.pixels().chunks(samples) {
+            let color = to_rgba(px);
+            let index = match colors.get(&color) {
+                Some(&index) => index,
+                None => {
+                    if palette_rgba.len() == 256 {
+                        return None;
+                    }
+                    let index = palette_rgba.len() as u8;
+                    colors.insert(color, index);
+                    palette_rgba.push(color);
+                    index
+                }
+            };
+            raw_indices.push(index);
+        }
+
+        let (depth, bits) = match palette_rgba.len() {
+            0..=2 => (png::BitDepth::One, 1),
+            3..=4 => (png::BitDepth::Two, 2),
+            5..=16 => (png::BitDepth::Four, 4),
+            _ => (png::BitDepth::Eight, 8),
+        };
+
+        // rows are packed msb first and padded to a whole byte
+        let per_byte = 8 / bits;
+        let width = info.width as usize;
+        let row_bytes = width.div_ceil(per_byte);
+        let mut indices = vec![0u8; row_bytes * info.height as usize];
+        for (row, row_indices) in raw_indices.chunks(width.max(1)).enumerate() {
+            for (col, &index) in row_indices.iter().enumerate() {
+                let shift = 8 - bits * (col % per_byte + 1);
+                indices[row * row_bytes + col / per_byte] |= index << shift;
+            }
+        }
+
+        // the alpha channel has to survive even if every pixel is opaque,
+        // otherwise the decoder would hand back rgb instead of rgba
+        let trns = if samples == 4 {
+            Some(palette_rgba.iter().map(|color| color[3]).collect())
+        } else {
+            None
+        };
+        let palette = palette_rgba
+            .iter()
+            .flat_map(|color| color[..3].to_vec())
+            .collect();
+
+        Some(Self {
+            palette,
+            trns,
+            depth,
+            indices,
+        })
+    }
+
+    fn encode(&self, page: &HieroPage, filter: png::FilterType) -> Result<Vec<u8>, Error> {
+        let info = page.info();
+        let indexed =
+            HieroPage::blank(info.width, info.height, 1).with_pixels(self.indices.clone());
+        encode(
+            &indexed,
+            png::ColorType::Indexed,
+            self.depth,
+            Some((self.palette.clone(), self.trns.clone())),
+            filter,
+        )
+    }
+}
+
+#[test]
+fn optimized_pages_decode_identically() {
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let original = HieroPage::decode_png(&page_file).unwrap();
+    let optimized = optimize_png(&page_file, &PngOptimizeOptions::default())
+        .unwrap()
+        .expect("hiero output should be compressible");
+    assert!(optimized.len() < page_file.len());
+
+    let decoded = HieroPage::decode_png(&optimized).unwrap();
+    assert_eq!(decoded.info().samples, original.info().samples);
+    assert_eq!(decoded.pixels(), original.pixels());
+}
+
+#[test]
+fn mips_follow_reencoded_pages() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let mut atlas = HieroAtlas::new()
+        .with_font_str(&font_file)
+        .with_pages(vec![page_file])
+        .build()
+        .unwrap();
+    atlas
+        .generate_mips(&GlyphMipOptions {
+            max_levels: Some(3),
+        })
+        .unwrap();
+    atlas.reencode_pages(PageEncoding::Raw).unwrap();
+    assert_eq!(atlas.mip_pages[0].len(), 2);
+    assert!(atlas.mip_pages[0]
+        .iter()
+        .all(|mip| mip.encoding == PageEncoding::Raw));
+    let levels = atlas
+        .build_glyph_mips(
+            0,
+            &GlyphMipOptions {
+                max_levels: Some(3),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        atlas.try_unpack_mips(0).unwrap()[2].pixels(),
+        levels[2].pixels()
+    );
+}
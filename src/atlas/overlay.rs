@@ -0,0 +1,140 @@
+use super::{draw::*, *};
+
+const BACKGROUND: [u8; 4] = rgba(32, 32, 32, 255);
+const FLAGGED: [u8; 4] = rgba(255, 0, 255, 255);
+const FLAGGED_FILL: [u8; 4] = rgba(255, 0, 255, 64);
+
+/// Knobs for [`HieroAtlas::render_overlays`]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OverlayOptions {
+    /// label every rect with its glyph, as the character itself where the built-in font has it and as the code point otherwise
+    pub annotate: bool,
+}
+
+/// outline color of a glyph rect, picked by the channel the glyph lives in
+pub fn channel_color(channel: i32) -> [u8; 4] {
+    match channel {
+        1 => rgba(64, 128, 255, 255),
+        2 => rgba(64, 220, 64, 255),
+        4 => rgba(255, 80, 64, 255),
+        8 => rgba(255, 220, 64, 255),
+        _ => rgba(0, 220, 220, 255),
+    }
+}
+
+impl HieroAtlas {
+    /// Draws every glyph rect onto an rgba copy of the page it belongs to, one image per page.\
+    /// Rects are outlined in [`channel_color`], the ones [`HieroAtlas::validate`] reports as out of bounds
+    /// or overlapping are outlined and tinted in magenta instead.
+    pub fn render_overlays(&self, options: &OverlayOptions) -> Result<Vec<HieroPage>, Error> {
+        let mut flagged = HashSet::new();
+        for diagnostic in self.validate() {
+            match diagnostic.kind {
+                DiagnosticKind::GlyphOutOfBounds { glyph, .. } => {
+                    flagged.insert(glyph);
+                }
+                DiagnosticKind::GlyphsOverlap { first, second, .. } => {
+                    flagged.insert(first);
+                    flagged.insert(second);
+                }
+                _ => (),
+            }
+        }
+
+        let mut glyphs: Vec<(GlyphId, &HieroBitmapInfo)> = self
+            .bitmap_table
+            .iter()
+            .map(|(&glyph, bitmap)| (glyph, bitmap))
+            .collect();
+        glyphs.sort_by_key(|&(glyph, _)| glyph);
+
+        let mut overlays = Vec::with_capacity(self.compressed_pages.len());
+        for page_index in 0..self.compressed_pages.len() {
+            let page = self.try_unpack_page_with(
+                page_index,
+                &UnpackOptions {
+                    color_space: Some(ColorSpace::Srgb),
+                    alpha_mode: Some(AlphaMode::Straight),
+                    force_rgba: true,
+                },
+            )?;
+            let info = page.info();
+            let mut overlay = HieroPage::blank(info.width, info.height, 4);
+            let size = (info.width as i32, info.height as i32);
+            fill_rect(&mut overlay, (0, 0), size, BACKGROUND);
+            for (index, texel) in page.pixels().chunks(4).enumerate() {
+                let (x, y) = (index as u32 % info.width, index as u32 / info.width);
+                let color = [texel[0], texel[1], texel[2], texel[3]];
+                blend(&mut overlay, x as i32, y as i32, color);
+            }
+
+            let on_page = glyphs
+                .iter()
+                .filter(|(_, bitmap)| bitmap.page as usize == page_index);
+            for &(glyph, bitmap) in on_page {
+                let (position, size) = ((bitmap.x, bitmap.y), (bitmap.width, bitmap.height));
+                if flagged.contains(&glyph) {
+                    fill_rect(&mut overlay, position, size, FLAGGED_FILL);
+                    stroke_rect(&mut overlay, position, size, FLAGGED);
+                } else {
+                    stroke_rect(&mut overlay, position, size, channel_color(bitmap.channel));
+                }
+                if options.annotate {
+                    let label = glyph_label(glyph);
+                    let label_position = (bitmap.x + 1, bitmap.y + 1);
+                    fill_rect(
+                        &mut overlay,
+                        (label_position.0 - 1, label_position.1 - 1),
+                        (text_width(&label) + 2, TINY_FONT_HEIGHT + 2),
+                        rgba(0, 0, 0, 160),
+                    );
+                    draw_text(
+                        &mut overlay,
+                        label_position,
+                        &label,
+                        rgba(255, 255, 255, 255),
+                    );
+                }
+            }
+            overlays.push(overlay);
+        }
+        Ok(overlays)
+    }
+}
+
+#[test]
+fn overlay_flags_bad_rects() {
+    let glyph = |x, y, channel| HieroBitmapInfo {
+        x,
+        y,
+        width: 4,
+        height: 4,
+        channel,
+        ..HieroBitmapInfo::default()
+    };
+    let mut atlas = HieroAtlas::default();
+    atlas.common.pages = 1;
+    atlas.common.scale_w = 16;
+    atlas.common.scale_h = 16;
+    atlas.bitmap_table.insert('a'.into(), glyph(0, 0, 4));
+    atlas.bitmap_table.insert('b'.into(), glyph(8, 8, 15));
+    atlas.bitmap_table.insert('c'.into(), glyph(10, 10, 15));
+    atlas.bitmap_table.insert('d'.into(), glyph(14, 0, 15));
+    let page = HieroPage::blank(16, 16, 4);
+    atlas.compressed_pages = vec![CompressedPage::encode(&page, PageEncoding::Raw).unwrap()];
+
+    let overlays = atlas.render_overlays(&OverlayOptions::default()).unwrap();
+    assert_eq!(overlays.len(), 1);
+    let texel = |x: usize, y: usize| overlays[0].pixels()[(y * 16 + x) * 4..][..4].to_vec();
+    assert_eq!(texel(0, 0), channel_color(4));
+    assert_eq!(texel(8, 8), FLAGGED);
+    assert_eq!(texel(13, 13), FLAGGED);
+    assert_eq!(texel(14, 0), FLAGGED);
+    assert_eq!(texel(5, 5), BACKGROUND);
+
+    // labels show the character when the built-in font has it
+    assert_eq!(glyph_label('A'.into()), "A");
+    assert_eq!(glyph_label('7'.into()), "7");
+    assert_eq!(glyph_label('a'.into()), "U+0061");
+    assert_eq!(glyph_label(GlyphId(0xD800)), "#55296");
+}
@@ -0,0 +1,105 @@
+/// A simple shelf packer.\
+/// Rects are placed left to right on horizontal shelves; when a page fills up a new one is started.
+pub struct ShelfPacker {
+    page_width: u32,
+    page_height: u32,
+    gutter: u32,
+    page: u32,
+    cursor_x: u32,
+    shelf_y: u32,
+    shelf_height: u32,
+    used_height: u32,
+}
+
+/// where a rect ended up after packing
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub page: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(page_width: u32, page_height: u32, gutter: u32) -> Self {
+        Self {
+            page_width,
+            page_height,
+            gutter,
+            page: 0,
+            cursor_x: gutter,
+            shelf_y: gutter,
+            shelf_height: 0,
+            used_height: 0,
+        }
+    }
+
+    /// number of pages touched so far
+    pub fn pages(&self) -> u32 {
+        self.page + 1
+    }
+
+    /// lowest pixel row used on the last page (gutter included)
+    pub fn used_height(&self) -> u32 {
+        self.used_height
+    }
+
+    /// returns `None` if the rect can't fit on an empty page
+    pub fn place(&mut self, width: u32, height: u32) -> Option<Placement> {
+        let gutter = self.gutter;
+        if width + 2 * gutter > self.page_width || height + 2 * gutter > self.page_height {
+            return None;
+        }
+        if self.cursor_x + width + gutter > self.page_width {
+            self.shelf_y += self.shelf_height + gutter;
+            self.cursor_x = gutter;
+            self.shelf_height = 0;
+        }
+        if self.shelf_y + height + gutter > self.page_height {
+            self.page += 1;
+            self.cursor_x = gutter;
+            self.shelf_y = gutter;
+            self.shelf_height = 0;
+            self.used_height = 0;
+        }
+        let placement = Placement {
+            page: self.page,
+            x: self.cursor_x,
+            y: self.shelf_y,
+        };
+        self.cursor_x += width + gutter;
+        self.shelf_height = self.shelf_height.max(height);
+        self.used_height = self.used_height.max(self.shelf_y + height + gutter);
+        Some(placement)
+    }
+}
+
+#[test]
+fn shelf_packing() {
+    let mut packer = ShelfPacker::new(11, 10, 1);
+    assert_eq!(
+        packer.place(4, 4),
+        Some(Placement {
+            page: 0,
+            x: 1,
+            y: 1
+        })
+    );
+    assert_eq!(
+        packer.place(4, 3),
+        Some(Placement {
+            page: 0,
+            x: 6,
+            y: 1
+        })
+    );
+    assert_eq!(
+        packer.place(4, 4),
+        Some(Placement {
+            page: 1,
+            x: 1,
+            y: 1
+        })
+    );
+    assert_eq!(packer.pages(), 2);
+    assert_eq!(packer.place(10, 1), None);
+}
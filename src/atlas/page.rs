@@ -1,26 +1,186 @@
 use super::*; 
 
-#[derive(Serialize, Deserialize, Default)]
+#[cfg(test)]
+mod tests;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct HieroPage {
     pixels: Vec<u8>,
     info: PageInfo,
+    palette: Option<Vec<u8>>,
+}
+
+/// Transformations applied while decoding png pages
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PngTransform {
+    /// expand palettes to rgb(a), sub-byte gray to 8 bits and `tRNS` chunks to an alpha channel
+    pub expand: bool,
+    /// drop the low byte of 16-bit samples
+    pub strip_16: bool,
+}
+
+impl PngTransform {
+    /// always yields 8-bit gray, gray+alpha, rgb or rgba pixels, what [`HieroAtlas::try_unpack_page`] uses
+    pub const NORMALIZE: Self = Self {
+        expand: true,
+        strip_16: true,
+    };
+    /// hands back the samples exactly as they are stored in the png
+    pub const FAITHFUL: Self = Self {
+        expand: false,
+        strip_16: false,
+    };
+
+    fn to_png(self) -> png::Transformations {
+        let mut transformations = png::Transformations::IDENTITY;
+        if self.expand {
+            transformations |= png::Transformations::EXPAND;
+        }
+        if self.strip_16 {
+            transformations |= png::Transformations::STRIP_16;
+        }
+        transformations
+    }
+}
+
+impl Default for PngTransform {
+    fn default() -> Self {
+        Self::NORMALIZE
+    }
 }
 
 
 impl HieroPage {
+    pub fn new(info: PageInfo, pixels: Vec<u8>) -> Self {
+        Self {
+            pixels,
+            info,
+            palette: None,
+        }
+    }
+
+    /// allocates a zeroed page of `width`x`height` with `samples` bytes per pixel
+    pub fn blank(width: u32, height: u32, samples: u32) -> Self {
+        let line_size = width * samples;
+        Self {
+            pixels: vec![0; (line_size * height) as usize],
+            info: PageInfo {
+                width,
+                height,
+                samples,
+                line_size,
+                color_type: PageColorType::from_samples(samples),
+                ..PageInfo::default()
+            },
+            palette: None,
+        }
+    }
+
     pub fn pixels(&self) -> &Vec<u8> {
         &self.pixels
     }
 
+    pub fn pixels_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.pixels
+    }
+
     pub fn info(&self) -> PageInfo {
         self.info
     }
 
+    /// rgba entries (4 bytes each) of an indexed page, transparency from `tRNS` already merged in
+    pub fn palette(&self) -> Option<&[u8]> {
+        self.palette.as_deref()
+    }
+
     pub fn with_pixels(mut self, data: Vec<u8>) -> Self {
         self.pixels = data;
         self
     }
+
+    /// records the color space and alpha mode the pixels are in (no conversion happens)
+    pub fn with_conventions(mut self, color_space: ColorSpace, alpha_mode: AlphaMode) -> Self {
+        self.info.color_space = color_space;
+        self.info.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// copies the `width`x`height` block at (`src_x`,`src_y`) of `src` to (`dst_x`,`dst_y`) of `self`\
+    /// both pages must have the same number of samples
+    pub fn blit(
+        &mut self,
+        src: &HieroPage,
+        (src_x, src_y): (u32, u32),
+        (dst_x, dst_y): (u32, u32),
+        (width, height): (u32, u32),
+    ) {
+        let samples = self.info.samples as usize;
+        let row_bytes = width as usize * samples;
+        for row in 0..height as usize {
+            let src_off = (src_y as usize + row) * src.info.line_size as usize + src_x as usize * samples;
+            let dst_off = (dst_y as usize + row) * self.info.line_size as usize + dst_x as usize * samples;
+            self.pixels[dst_off..dst_off + row_bytes]
+                .copy_from_slice(&src.pixels[src_off..src_off + row_bytes]);
+        }
+    }
+
+    /// decodes png bytes into an 8-bit page (palettes are expanded and 16-bit samples are stripped)
+    pub fn decode_png(data: &[u8]) -> Result<Self, Error> {
+        Self::decode_png_with(data, PngTransform::NORMALIZE)
+    }
+
+    /// decodes png bytes, `transform` decides how much of the original bit depth and color type survives
+    pub fn decode_png_with(data: &[u8], transform: PngTransform) -> Result<Self, Error> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(transform.to_png());
+        let (png_info, mut reader) = decoder
+            .read_info()
+            .map_err(PageUnpackError::PageDecodeError)?;
+        let mut page_buffer = vec![0; png_info.buffer_size()];
+        reader
+            .next_frame(&mut page_buffer)
+            .map_err(PageUnpackError::PageDecodeError)?;
+
+        let palette = if png_info.color_type == png::ColorType::Indexed {
+            let info = reader.info();
+            let rgb = info.palette.as_deref().unwrap_or(&[]);
+            let trns = info.trns.as_deref().unwrap_or(&[]);
+            let rgba = rgb
+                .chunks(3)
+                .enumerate()
+                .flat_map(|(i, c)| [c[0], c[1], c[2], trns.get(i).copied().unwrap_or(255)])
+                .collect();
+            Some(rgba)
+        } else {
+            None
+        };
+
+        let mut page = HieroPage::from(png_info).with_pixels(page_buffer);
+        page.palette = palette;
+        Ok(page)
+    }
+
+    /// encodes the page as a png with the same bit depth and color type
+    pub fn encode_png(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.info.width, self.info.height);
+            encoder.set_color(self.info.color_type.into());
+            encoder.set_depth(
+                png::BitDepth::from_u8(self.info.bit_depth)
+                    .ok_or(Error::InvalidBitDepth(self.info.bit_depth))?,
+            );
+            if let Some(palette) = &self.palette {
+                encoder.set_palette(palette.chunks(4).flat_map(|c| c[..3].to_vec()).collect());
+                encoder.set_trns(palette.chunks(4).map(|c| c[3]).collect());
+            }
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(bytes)
+    }
 }
+
 impl From<png::OutputInfo> for HieroPage {
     fn from(info: png::OutputInfo) -> Self {
         Self {
@@ -30,7 +190,11 @@ impl From<png::OutputInfo> for HieroPage {
                 height: info.height,
                 samples: info.color_type.samples() as u32,
                 line_size: info.line_size as u32,
+                bit_depth: info.bit_depth as u8,
+                color_type: info.color_type.into(),
+                ..PageInfo::default()
             },
+            palette: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+use super::*;
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(format!("./fixtures/png/{}.png", name)).unwrap()
+}
+
+fn describe(page: &HieroPage) -> (PageColorType, u8, u32, u32) {
+    let info = page.info();
+    (
+        info.color_type,
+        info.bit_depth,
+        info.samples,
+        info.line_size,
+    )
+}
+
+#[test]
+fn gray8() {
+    let page = HieroPage::decode_png(&fixture("gray8")).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Grayscale, 8, 1, 4));
+    assert_eq!(page.pixels()[..], [0, 32, 64, 96, 128, 160, 192, 224]);
+}
+
+#[test]
+fn gray16() {
+    let data = fixture("gray16");
+    let page = HieroPage::decode_png(&data).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Grayscale, 8, 1, 4));
+    assert_eq!(page.pixels()[..2], [0x00, 0x20]);
+
+    let page = HieroPage::decode_png_with(&data, PngTransform::FAITHFUL).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Grayscale, 16, 1, 8));
+    assert_eq!(page.pixels()[..4], [0x00, 0x12, 0x20, 0x12]);
+}
+
+#[test]
+fn rgba16() {
+    let data = fixture("rgba16");
+    let page = HieroPage::decode_png(&data).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Rgba, 8, 4, 16));
+    assert_eq!(page.pixels()[4..8], [0x20, 0xdf, 0x80, 0xff]);
+
+    let page = HieroPage::decode_png_with(&data, PngTransform::FAITHFUL).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Rgba, 16, 4, 32));
+
+    // re-encoding keeps all 16 bits
+    let reencoded =
+        HieroPage::decode_png_with(&page.encode_png().unwrap(), PngTransform::FAITHFUL).unwrap();
+    assert_eq!(reencoded.pixels(), page.pixels());
+}
+
+#[test]
+fn indexed() {
+    let data = fixture("indexed2");
+    let page = HieroPage::decode_png(&data).unwrap();
+    // the last palette entry is transparent so expansion adds an alpha channel
+    assert_eq!(describe(&page), (PageColorType::Rgba, 8, 4, 16));
+    assert_eq!(page.pixels()[..8], [255, 0, 0, 255, 0, 255, 0, 255]);
+    assert_eq!(page.pixels()[12..16], [255, 255, 255, 0]);
+    assert!(page.palette().is_none());
+
+    let page = HieroPage::decode_png_with(&data, PngTransform::FAITHFUL).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Indexed, 2, 1, 1));
+    assert_eq!(page.pixels()[..], [0b00_01_10_11, 0b11_10_01_00]);
+    let palette = page.palette().unwrap();
+    assert_eq!(palette.len(), 16);
+    assert_eq!(palette[12..], [255, 255, 255, 0]);
+
+    let reencoded =
+        HieroPage::decode_png_with(&page.encode_png().unwrap(), PngTransform::FAITHFUL).unwrap();
+    assert_eq!(reencoded.pixels(), page.pixels());
+    assert_eq!(reencoded.palette(), page.palette());
+}
+
+#[test]
+fn gray1() {
+    let data = fixture("gray1");
+    let page = HieroPage::decode_png(&data).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Grayscale, 8, 1, 4));
+    assert_eq!(page.pixels()[..], [255, 0, 255, 0, 0, 255, 0, 255]);
+
+    let page = HieroPage::decode_png_with(&data, PngTransform::FAITHFUL).unwrap();
+    assert_eq!(describe(&page), (PageColorType::Grayscale, 1, 1, 1));
+}
@@ -0,0 +1,250 @@
+use super::{draw::*, *};
+
+pub const PANGRAM: &str = "The quick brown fox jumps over the lazy dog. 0123456789";
+
+const BACKGROUND: [u8; 4] = rgba(32, 32, 32, 255);
+const CELL_BORDER: [u8; 4] = rgba(64, 64, 64, 255);
+const LABEL: [u8; 4] = rgba(160, 160, 160, 255);
+const GLYPH_RECT: [u8; 4] = rgba(255, 64, 64, 192);
+const ADVANCE_BOX: [u8; 4] = rgba(64, 128, 255, 160);
+const BASELINE: [u8; 4] = rgba(64, 200, 64, 160);
+const MARGIN: i32 = 4;
+
+/// Knobs for [`HieroAtlas::render_preview`]
+#[derive(Clone, Debug)]
+pub struct PreviewOptions {
+    /// glyph cells per row
+    pub columns: u32,
+    /// paragraph set with the font below the grid, skipped when empty
+    pub sample_text: String,
+    /// draw the glyph rect, the advance box and the baseline of every cell
+    pub boxes: bool,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            columns: 16,
+            sample_text: String::from(PANGRAM),
+            boxes: true,
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// Renders a contact sheet of every glyph, labelled with its code point, followed by the sample text.\
+    /// The sheet is an 8-bit rgba page, glyphs that point at a missing page only get their boxes drawn.
+    pub fn render_preview(&self, options: &PreviewOptions) -> Result<HieroPage, Error> {
+        let pages = (0..self.compressed_pages.len())
+            .map(|page_index| {
+                self.try_unpack_page_with(
+                    page_index,
+                    &UnpackOptions {
+                        color_space: Some(ColorSpace::Srgb),
+                        alpha_mode: Some(AlphaMode::Straight),
+                        force_rgba: true,
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut glyphs: Vec<(GlyphId, &HieroBitmapInfo)> = self
+            .bitmap_table
+            .iter()
+            .map(|(&glyph, bitmap)| (glyph, bitmap))
+            .collect();
+        glyphs.sort_by_key(|&(glyph, _)| glyph);
+
+        let line_height = self.common.line_height.max(1);
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = (0, 1, 0, line_height);
+        let mut label_width = 0;
+        for &(glyph, bitmap) in glyphs.iter() {
+            min_x = min_x.min(bitmap.xoffset);
+            max_x = max_x
+                .max(bitmap.xadvance)
+                .max(bitmap.xoffset + bitmap.width);
+            min_y = min_y.min(bitmap.yoffset);
+            max_y = max_y.max(bitmap.yoffset + bitmap.height);
+            label_width = label_width.max(text_width(&code_point_label(glyph)));
+        }
+        let label_height = TINY_FONT_HEIGHT + 2;
+        let cell_width = (max_x - min_x).max(label_width) + 2 * MARGIN;
+        let cell_height = label_height + (max_y - min_y) + 2 * MARGIN;
+        let columns = options.columns.max(1) as i32;
+        let rows = (glyphs.len() as i32 + columns - 1) / columns;
+        let sheet_width = columns * cell_width;
+
+        let sample = self.layout_text(&options.sample_text, sheet_width - 2 * MARGIN);
+        let sample_lines = sample
+            .iter()
+            .map(|&(_, _, line)| line + 1)
+            .max()
+            .unwrap_or(0);
+        let sample_height = if sample_lines > 0 {
+            sample_lines * line_height + 2 * MARGIN
+        } else {
+            0
+        };
+
+        let mut sheet = HieroPage::blank(
+            sheet_width as u32,
+            (rows * cell_height + sample_height).max(1) as u32,
+            4,
+        );
+        fill_rect(
+            &mut sheet,
+            (0, 0),
+            (sheet_width, rows * cell_height + sample_height),
+            BACKGROUND,
+        );
+
+        for (index, &(glyph, bitmap)) in glyphs.iter().enumerate() {
+            let cell_x = (index as i32 % columns) * cell_width;
+            let cell_y = (index as i32 / columns) * cell_height;
+            let origin = (
+                cell_x + MARGIN - min_x,
+                cell_y + MARGIN + label_height - min_y,
+            );
+            stroke_rect(
+                &mut sheet,
+                (cell_x, cell_y),
+                (cell_width, cell_height),
+                CELL_BORDER,
+            );
+            draw_text(
+                &mut sheet,
+                (cell_x + MARGIN, cell_y + MARGIN),
+                &code_point_label(glyph),
+                LABEL,
+            );
+            if options.boxes {
+                fill_rect(
+                    &mut sheet,
+                    (cell_x + 1, origin.1 + self.common.base),
+                    (cell_width - 2, 1),
+                    BASELINE,
+                );
+                stroke_rect(
+                    &mut sheet,
+                    origin,
+                    (bitmap.xadvance, line_height),
+                    ADVANCE_BOX,
+                );
+            }
+            if let Some(page) = pages.get(bitmap.page as usize) {
+                let dest = (origin.0 + bitmap.xoffset, origin.1 + bitmap.yoffset);
+                draw_glyph(&mut sheet, page, bitmap, &self.common, dest);
+            }
+            if options.boxes {
+                stroke_rect(
+                    &mut sheet,
+                    (origin.0 + bitmap.xoffset, origin.1 + bitmap.yoffset),
+                    (bitmap.width, bitmap.height),
+                    GLYPH_RECT,
+                );
+            }
+        }
+
+        let sample_top = rows * cell_height + MARGIN;
+        if options.boxes {
+            for line in 0..sample_lines {
+                fill_rect(
+                    &mut sheet,
+                    (0, sample_top + line * line_height + self.common.base),
+                    (sheet_width, 1),
+                    BASELINE,
+                );
+            }
+        }
+        for &(glyph, pen_x, line) in sample.iter() {
+            let bitmap = &self.bitmap_table[&glyph.into()];
+            if let Some(page) = pages.get(bitmap.page as usize) {
+                let dest = (
+                    MARGIN + pen_x + bitmap.xoffset,
+                    sample_top + line * line_height + bitmap.yoffset,
+                );
+                draw_glyph(&mut sheet, page, bitmap, &self.common, dest);
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// places the characters of `text` the font has a glyph for, returns (glyph, pen x, line) triples.\
+    /// Kerning is applied and lines wrap at character granularity once they would exceed `width`.
+    fn layout_text(&self, text: &str, width: i32) -> Vec<(char, i32, i32)> {
+        let mut placed = Vec::new();
+        let (mut pen_x, mut line) = (0, 0);
+        let mut previous = None;
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = 0;
+                line += 1;
+                previous = None;
+                continue;
+            }
+            let bitmap = match self.glyph(c) {
+                Some(bitmap) => bitmap,
+                None => continue,
+            };
+            if let Some(previous) = previous {
+                pen_x += self.kerning(previous, c);
+            }
+            if pen_x > 0 && pen_x + bitmap.xadvance > width {
+                pen_x = 0;
+                line += 1;
+            }
+            placed.push((c, pen_x, line));
+            pen_x += bitmap.xadvance;
+            previous = Some(c);
+        }
+        placed
+    }
+}
+
+#[test]
+fn preview_sheet() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let atlas = HieroAtlas::new()
+        .with_font_file(font_file)
+        .with_pages(vec![page_file])
+        .build()
+        .unwrap();
+    let grid_only = atlas
+        .render_preview(&PreviewOptions {
+            columns: 10,
+            sample_text: String::new(),
+            boxes: true,
+        })
+        .unwrap();
+    let info = grid_only.info();
+    assert_eq!(info.samples, 4);
+    assert_eq!(info.width % 10, 0);
+    let rows = (atlas.bitmap_table.len() as u32).div_ceil(10);
+    assert_eq!(info.height % rows, 0);
+    let cell_width = (info.width / 10) as i32;
+    assert!(
+        cell_width
+            >= atlas
+                .bitmap_table
+                .values()
+                .map(|g| g.xadvance)
+                .max()
+                .unwrap()
+    );
+
+    let sheet = atlas
+        .render_preview(&PreviewOptions {
+            columns: 10,
+            ..PreviewOptions::default()
+        })
+        .unwrap();
+    assert_eq!(sheet.info().width, info.width);
+    assert!(sheet.info().height > info.height);
+    // the sample text below the grid puts white ink on the sheet
+    let below_grid = &sheet.pixels()[(info.height * info.line_size) as usize..];
+    assert!(below_grid
+        .chunks(4)
+        .any(|px| px[0] > 200 && px[1] > 200 && px[2] > 200));
+}
@@ -0,0 +1,202 @@
+use super::{packer::ShelfPacker, *};
+
+impl HieroAtlas {
+    /// Returns a copy of the atlas that only contains the glyphs found in `charset`.\
+    /// Glyphs whose id isn't a character (like icons at surrogate or negative ids) can't be named by a charset and are kept.
+    /// Kerning pairs and names referencing dropped glyphs are removed and the surviving glyphs are repacked
+    /// into as few pages as possible (a lone page is also trimmed down to a power-of-two height).
+    pub fn subset(&self, charset: &Charset) -> Result<HieroAtlas, Error> {
+        let pages = (0..self.compressed_pages.len())
+            .map(|page_index| self.try_unpack_page(page_index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let bitmap_table: HashMap<GlyphId, HieroBitmapInfo> = self
+            .bitmap_table
+            .iter()
+            .filter(|(key, _)| key.as_char().is_none_or(|c| charset.contains(c)))
+            .map(|(&key, &glyph)| (key, glyph))
+            .collect();
+
+        let kerning_table = self
+            .kerning_table
+            .iter()
+            .filter(|((first, second), _)| {
+                bitmap_table.contains_key(first) && bitmap_table.contains_key(second)
+            })
+            .map(|(&pair, &amount)| (pair, amount))
+            .collect();
+        let mut glyph_names = self.glyph_names.clone();
+        glyph_names.retain(|id| bitmap_table.contains_key(&id));
+
+        let samples = pages.first().map(|page| page.info().samples).unwrap_or(4);
+        if pages.iter().any(|page| page.info().samples != samples) {
+            return Err(Error::MismatchedPageFormats);
+        }
+
+        let (page_width, page_height) = match pages.first() {
+            Some(_) if self.common.scale_w > 0 && self.common.scale_h > 0 => {
+                (self.common.scale_w as u32, self.common.scale_h as u32)
+            }
+            Some(page) => (page.info().width, page.info().height),
+            None => (0, 0),
+        };
+        let gutter = self.info.spacing.iter().copied().max().unwrap_or(0).max(1) as u32;
+
+        // tallest glyphs first keeps the shelves tight
+        let mut order: Vec<GlyphId> = bitmap_table
+            .iter()
+            .filter(|(_, glyph)| glyph.width > 0 && glyph.height > 0)
+            .map(|(&key, _)| key)
+            .collect();
+        order.sort_by_key(|key| {
+            let glyph = &bitmap_table[key];
+            (
+                std::cmp::Reverse(glyph.height),
+                std::cmp::Reverse(glyph.width),
+                *key,
+            )
+        });
+
+        let mut packer = ShelfPacker::new(page_width, page_height, gutter);
+        let mut placements = Vec::with_capacity(order.len());
+        for &key in &order {
+            let glyph = &bitmap_table[&key];
+            check_glyph_bounds(key, glyph, &pages)?;
+            let placement = packer
+                .place(glyph.width as u32, glyph.height as u32)
+                .ok_or(Error::GlyphDoesntFit(key))?;
+            placements.push((key, placement));
+        }
+
+        let page_count = if order.is_empty() { 0 } else { packer.pages() };
+        let out_height = if page_count == 1 {
+            packer.used_height().next_power_of_two().min(page_height)
+        } else {
+            page_height
+        };
+
+        let mut out_pages: Vec<HieroPage> = (0..page_count)
+            .map(|_| HieroPage::blank(page_width, out_height, samples))
+            .collect();
+        let mut subset = HieroAtlas {
+            info: self.info.clone(),
+            common: self.common.clone(),
+            bitmap_table,
+            kerning_table,
+            glyph_names,
+            compressed_pages: Vec::new(),
+            // mips and exported textures hold the old layout
+            mip_pages: Vec::new(),
+            textures: Vec::new(),
+        };
+
+        for (key, placement) in placements {
+            let glyph = subset.bitmap_table.get_mut(&key).unwrap();
+            out_pages[placement.page as usize].blit(
+                &pages[glyph.page as usize],
+                (glyph.x as u32, glyph.y as u32),
+                (placement.x, placement.y),
+                (glyph.width as u32, glyph.height as u32),
+            );
+            glyph.x = placement.x as i32;
+            glyph.y = placement.y as i32;
+            glyph.page = placement.page as i32;
+        }
+
+        // empty glyphs (like space) don't own any pixels
+        for glyph in subset.bitmap_table.values_mut() {
+            if glyph.width <= 0 || glyph.height <= 0 {
+                glyph.x = 0;
+                glyph.y = 0;
+                if page_count > 0 {
+                    glyph.page = 0;
+                }
+            }
+        }
+
+        let encoding = self
+            .compressed_pages
+            .first()
+            .map_or(PageEncoding::Png, |page| page.encoding);
+        subset.compressed_pages = out_pages
+            .iter()
+            .map(|page| CompressedPage::encode(page, encoding))
+            .collect::<Result<_, _>>()?;
+        subset.common.pages = page_count as i32;
+        subset.common.scale_h = out_height as i32;
+
+        Ok(subset)
+    }
+}
+
+fn check_glyph_bounds(
+    key: GlyphId,
+    glyph: &HieroBitmapInfo,
+    pages: &[HieroPage],
+) -> Result<(), Error> {
+    let page = pages
+        .get(glyph.page as usize)
+        .filter(|_| glyph.page >= 0)
+        .ok_or(Error::MissingPage(key))?;
+    let info = page.info();
+    let inside = glyph.x >= 0
+        && glyph.y >= 0
+        && (glyph.x + glyph.width) as u32 <= info.width
+        && (glyph.y + glyph.height) as u32 <= info.height;
+    if inside {
+        Ok(())
+    } else {
+        Err(Error::GlyphOutsidePage(key))
+    }
+}
+
+#[test]
+fn subset_keeps_glyph_pixels() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let mut atlas = HieroAtlas::new()
+        .with_pages(vec![page_file])
+        .with_font_file(font_file)
+        .build()
+        .unwrap();
+
+    // nothing left to pack, so empty glyphs aren't moved to a page the subset doesn't have
+    let spaces = atlas.subset(&Charset::from_chars(" ")).unwrap();
+    assert!(spaces.compressed_pages.is_empty());
+    assert_eq!(spaces.common.pages, 0);
+    assert_eq!(
+        spaces.glyph(' ').unwrap().page,
+        atlas.glyph(' ').unwrap().page
+    );
+
+    // an icon at an id no charset can name survives the subset
+    let icon = GlyphId(0xD800);
+    let icon_glyph = *atlas.glyph('H').unwrap();
+    atlas.bitmap_table.insert(icon, icon_glyph);
+
+    let subset = atlas.subset(&Charset::from_chars("Hello ")).unwrap();
+    let mut keys: Vec<char> = subset.char_glyphs().map(|(c, _)| c).collect();
+    keys.sort();
+    assert_eq!(keys, vec![' ', 'H', 'e', 'l', 'o']);
+    assert!(subset.glyph(icon).is_some());
+    assert_eq!(subset.compressed_pages.len(), 1);
+    assert!(subset.common.scale_h < atlas.common.scale_h);
+
+    let old_page = atlas.try_unpack_page(0).unwrap();
+    let new_page = subset.try_unpack_page(0).unwrap();
+    for key in "Helo".chars().map(GlyphId::from).chain(Some(icon)) {
+        let (old, new) = (atlas.glyph(key).unwrap(), subset.glyph(key).unwrap());
+        let samples = old_page.info().samples as i32;
+        for row in 0..old.height {
+            let old_off =
+                ((old.y + row) * old_page.info().line_size as i32 + old.x * samples) as usize;
+            let new_off =
+                ((new.y + row) * new_page.info().line_size as i32 + new.x * samples) as usize;
+            let len = (old.width * samples) as usize;
+            assert_eq!(
+                old_page.pixels()[old_off..old_off + len],
+                new_page.pixels()[new_off..new_off + len]
+            );
+        }
+    }
+}
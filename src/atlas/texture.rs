@@ -0,0 +1,461 @@
+use super::*;
+use std::path::Path;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+const DXGI_FORMAT_R8G8B8A8_UNORM: u32 = 28;
+const DXGI_FORMAT_R8G8B8A8_UNORM_SRGB: u32 = 29;
+
+/// GPU texture container formats pages can be exported to
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureContainer {
+    Ktx2,
+    Dds,
+}
+
+impl TextureContainer {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ktx2 => "ktx2",
+            Self::Dds => "dds",
+        }
+    }
+}
+
+impl std::str::FromStr for TextureContainer {
+    type Err = Error;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "ktx2" => Ok(Self::Ktx2),
+            "dds" => Ok(Self::Dds),
+            _ => Err(Error::UnknownTextureContainer(name.to_string())),
+        }
+    }
+}
+
+/// Where an exported texture lives and which pages it holds.\
+/// `path` is relative to the directory the textures were exported to, `pages` lists the atlas
+/// pages stored in the file in layer order (a single page unless it's a texture array).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TextureRef {
+    pub container: TextureContainer,
+    pub path: String,
+    pub pages: Vec<u32>,
+    pub mip_levels: u32,
+    /// the conventions of the texels, the texture format and header say the same
+    pub color_space: ColorSpace,
+    pub alpha_mode: AlphaMode,
+}
+
+/// Knobs for [`HieroAtlas::export_textures`]
+#[derive(Copy, Clone, Debug)]
+pub struct TextureExportOptions {
+    pub container: TextureContainer,
+    /// store every page as a layer of one texture array instead of one file per page
+    pub array: bool,
+    /// export mip levels too, the glyph aware ones from [`HieroAtlas::generate_mips`] if there are any,
+    /// otherwise a plain box filtered chain
+    pub mipmaps: bool,
+    /// color space of the texels, `None` keeps the one the pages are stored in.
+    /// Pages stored in another color space are converted
+    pub color_space: Option<ColorSpace>,
+    /// alpha mode of the texels, `None` keeps the one the pages are stored in
+    pub alpha_mode: Option<AlphaMode>,
+}
+
+impl Default for TextureExportOptions {
+    fn default() -> Self {
+        Self {
+            container: TextureContainer::Ktx2,
+            array: false,
+            mipmaps: true,
+            color_space: None,
+            alpha_mode: None,
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// Writes the pages as rgba8 textures to `dir` (files are named after `name`) and records them
+    /// in [`HieroAtlas::textures`], replacing anything exported before.\
+    /// Textures are tagged with the color space and alpha mode of their texels (srgb formats, the ktx2
+    /// premultiplied flag, the dds alpha mode), pages stored otherwise than `options` asks are converted.
+    pub fn export_textures<P: AsRef<Path>>(
+        &mut self,
+        dir: P,
+        name: &str,
+        options: &TextureExportOptions,
+    ) -> Result<&[TextureRef], Error> {
+        let conversion = UnpackOptions {
+            color_space: options.color_space,
+            alpha_mode: options.alpha_mode,
+            force_rgba: true,
+        };
+        let layers = (0..self.compressed_pages.len())
+            .map(|page_index| {
+                if !options.mipmaps {
+                    return Ok(vec![self.try_unpack_page_with(page_index, &conversion)?]);
+                }
+                let levels = self.try_unpack_mips(page_index)?;
+                Ok(if levels.len() > 1 {
+                    levels
+                        .iter()
+                        .map(|level| level.convert(&conversion))
+                        .collect()
+                } else {
+                    levels[0].convert(&conversion).mip_chain()
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let groups: Vec<Vec<usize>> = if options.array {
+            vec![(0..layers.len()).collect()]
+        } else {
+            (0..layers.len()).map(|page| vec![page]).collect()
+        };
+
+        let mut textures = Vec::with_capacity(groups.len());
+        for group in groups.into_iter().filter(|group| !group.is_empty()) {
+            let group_layers: Vec<&[HieroPage]> = group.iter().map(|&i| &layers[i][..]).collect();
+            let file_name = if options.array {
+                format!("{}.{}", name, options.container.extension())
+            } else {
+                format!(
+                    "{}_page{}.{}",
+                    name,
+                    group[0],
+                    options.container.extension()
+                )
+            };
+            let bytes = match options.container {
+                TextureContainer::Ktx2 => write_ktx2(&group_layers, options.array)?,
+                TextureContainer::Dds => write_dds(&group_layers, options.array)?,
+            };
+            let info = group_layers[0][0].info();
+            std::fs::write(dir.as_ref().join(&file_name), bytes)?;
+            textures.push(TextureRef {
+                container: options.container,
+                path: file_name,
+                pages: group.iter().map(|&page| page as u32).collect(),
+                mip_levels: group_layers[0].len() as u32,
+                color_space: info.color_space,
+                alpha_mode: info.alpha_mode,
+            });
+        }
+
+        self.textures = textures;
+        Ok(&self.textures)
+    }
+}
+
+/// checks that every layer has the same size, number of levels and conventions,
+/// returns the base level info and the number of levels
+fn layer_info(layers: &[&[HieroPage]]) -> Result<(PageInfo, usize), Error> {
+    let base = layers
+        .first()
+        .and_then(|levels| levels.first())
+        .ok_or(Error::NoPages)?
+        .info();
+    let levels = layers[0].len();
+    let uniform = layers.iter().all(|layer| {
+        layer.len() == levels
+            && layer[0].info().width == base.width
+            && layer[0].info().height == base.height
+            && layer.iter().all(|level| {
+                let info = level.info();
+                info.samples == 4
+                    && info.color_space == base.color_space
+                    && info.alpha_mode == base.alpha_mode
+            })
+    });
+    if uniform {
+        Ok((base, levels))
+    } else {
+        Err(Error::MismatchedTextureLayers)
+    }
+}
+
+/// Serializes rgba8 layers (each a list of mip levels) into a ktx2 container.\
+/// The format and data format descriptor follow the color space and alpha mode of the pages.
+pub fn write_ktx2(layers: &[&[HieroPage]], array: bool) -> Result<Vec<u8>, Error> {
+    let (base, level_count) = layer_info(layers)?;
+    let (width, height) = (base.width, base.height);
+    let srgb = base.color_space == ColorSpace::Srgb;
+
+    let dfd = ktx2_rgba8_dfd(srgb, base.alpha_mode == AlphaMode::Premultiplied);
+    let level_index_offset = 80;
+    let dfd_offset = level_index_offset + 24 * level_count;
+    let data_offset = dfd_offset + dfd.len();
+
+    // mip levels are stored smallest first, every level holds all of its layers back to back
+    let level_sizes: Vec<usize> = (0..level_count)
+        .map(|level| layers.iter().map(|layer| layer[level].pixels().len()).sum())
+        .collect();
+    let mut level_offsets = vec![0; level_count];
+    let mut cursor = data_offset;
+    for level in (0..level_count).rev() {
+        level_offsets[level] = cursor;
+        cursor += level_sizes[level];
+    }
+
+    let mut out = Vec::with_capacity(cursor);
+    out.extend_from_slice(&KTX2_IDENTIFIER);
+    let format = if srgb {
+        VK_FORMAT_R8G8B8A8_SRGB
+    } else {
+        VK_FORMAT_R8G8B8A8_UNORM
+    };
+    let layer_count = if array { layers.len() as u32 } else { 0 };
+    for word in [
+        format,
+        1,
+        width,
+        height,
+        0,
+        layer_count,
+        1,
+        level_count as u32,
+        0,
+    ]
+    .iter()
+    {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    // dfd, kvd (unused) and sgd (unused)
+    for word in [dfd_offset as u32, dfd.len() as u32, 0, 0].iter() {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    out.extend_from_slice(&0u64.to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes());
+    for level in 0..level_count {
+        for value in [level_offsets[level], level_sizes[level], level_sizes[level]].iter() {
+            out.extend_from_slice(&(*value as u64).to_le_bytes());
+        }
+    }
+    out.extend_from_slice(&dfd);
+    for level in (0..level_count).rev() {
+        for layer in layers {
+            out.extend_from_slice(layer[level].pixels());
+        }
+    }
+    Ok(out)
+}
+
+/// the basic data format descriptor of an rgba8 texture
+fn ktx2_rgba8_dfd(srgb: bool, premultiplied: bool) -> Vec<u8> {
+    const BLOCK_SIZE: u32 = 24 + 16 * 4;
+    const KHR_DF_MODEL_RGBSDA: u32 = 1;
+    const KHR_DF_PRIMARIES_BT709: u32 = 1;
+    const KHR_DF_FLAG_ALPHA_PREMULTIPLIED: u32 = 1;
+    const KHR_DF_SAMPLE_DATATYPE_LINEAR: u32 = 0x10;
+    let transfer = if srgb { 2 } else { 1 };
+    let flags = if premultiplied {
+        KHR_DF_FLAG_ALPHA_PREMULTIPLIED
+    } else {
+        0
+    };
+
+    let mut words = vec![
+        4 + BLOCK_SIZE,
+        0,
+        2 | (BLOCK_SIZE << 16),
+        KHR_DF_MODEL_RGBSDA | (KHR_DF_PRIMARIES_BT709 << 8) | (transfer << 16) | (flags << 24),
+        0,
+        4,
+        0,
+    ];
+    for (index, &channel) in [0u32, 1, 2, 15].iter().enumerate() {
+        // alpha is never srgb encoded
+        let qualifiers = if srgb && channel == 15 {
+            KHR_DF_SAMPLE_DATATYPE_LINEAR
+        } else {
+            0
+        };
+        words.push((index as u32 * 8) | (7 << 16) | ((channel | qualifiers) << 24));
+        words.push(0);
+        words.push(0);
+        words.push(255);
+    }
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Serializes rgba8 layers (each a list of mip levels) into a dds container.\
+/// The format and alpha mode of the dx10 header follow the conventions of the pages.
+pub fn write_dds(layers: &[&[HieroPage]], array: bool) -> Result<Vec<u8>, Error> {
+    const DDSD_CAPS: u32 = 0x1;
+    const DDSD_HEIGHT: u32 = 0x2;
+    const DDSD_WIDTH: u32 = 0x4;
+    const DDSD_PITCH: u32 = 0x8;
+    const DDSD_PIXELFORMAT: u32 = 0x1000;
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS_COMPLEX: u32 = 0x8;
+    const DDSCAPS_TEXTURE: u32 = 0x1000;
+    const DDSCAPS_MIPMAP: u32 = 0x400000;
+    const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+    const DDS_ALPHA_MODE_STRAIGHT: u32 = 1;
+    const DDS_ALPHA_MODE_PREMULTIPLIED: u32 = 2;
+
+    let (base, level_count) = layer_info(layers)?;
+    let (width, height) = (base.width, base.height);
+    let srgb = base.color_space == ColorSpace::Srgb;
+    let premultiplied = base.alpha_mode == AlphaMode::Premultiplied;
+    // the legacy header can't express arrays, srgb or premultiplied alpha
+    let dx10 = array || srgb || premultiplied;
+
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PITCH | DDSD_PIXELFORMAT;
+    let mut caps = DDSCAPS_TEXTURE;
+    if level_count > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+    if array {
+        caps |= DDSCAPS_COMPLEX;
+    }
+
+    let mut words = vec![124, flags, height, width, width * 4, 0, level_count as u32];
+    words.extend_from_slice(&[0; 11]);
+    if dx10 {
+        words.extend_from_slice(&[32, DDPF_FOURCC, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0]);
+    } else {
+        words.extend_from_slice(&[
+            32,
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            32,
+            0x0000_00ff,
+            0x0000_ff00,
+            0x00ff_0000,
+            0xff00_0000,
+        ]);
+    }
+    words.extend_from_slice(&[caps, 0, 0, 0, 0]);
+    if dx10 {
+        let format = if srgb {
+            DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+        } else {
+            DXGI_FORMAT_R8G8B8A8_UNORM
+        };
+        let alpha_mode = if premultiplied {
+            DDS_ALPHA_MODE_PREMULTIPLIED
+        } else {
+            DDS_ALPHA_MODE_STRAIGHT
+        };
+        words.extend_from_slice(&[
+            format,
+            D3D10_RESOURCE_DIMENSION_TEXTURE2D,
+            0,
+            layers.len() as u32,
+            alpha_mode,
+        ]);
+    }
+
+    let mut out = b"DDS ".to_vec();
+    out.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+    // every layer stores its whole mip chain, largest level first
+    for layer in layers {
+        for level in layer.iter() {
+            out.extend_from_slice(level.pixels());
+        }
+    }
+    Ok(out)
+}
+
+#[test]
+fn ktx2_layout() {
+    let page = HieroPage::blank(4, 2, 4).with_conventions(ColorSpace::Linear, AlphaMode::Straight);
+    let levels = page.mip_chain();
+    let bytes = write_ktx2(&[&levels[..], &levels[..]], true).unwrap();
+    let word = |offset: usize| {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    assert_eq!(&bytes[..12], &KTX2_IDENTIFIER);
+    assert_eq!(word(12), VK_FORMAT_R8G8B8A8_UNORM);
+    assert_eq!((word(20), word(24)), (4, 2));
+    assert_eq!(word(32), 2); // layers
+    assert_eq!(word(40), 3); // levels
+    assert_eq!(word(48) as usize, 80 + 24 * 3); // dfd offset
+    assert_eq!(word(52), 92);
+    let base_offset = word(80) as usize;
+    let base_length = word(88) as usize;
+    assert_eq!(base_length, 2 * 4 * 2 * 4);
+    assert_eq!(base_offset + base_length, bytes.len());
+    assert_eq!(word(80 + 24 * 3 + 12) >> 16, 1); // linear transfer, not premultiplied
+
+    // srgb premultiplied pages pick the srgb format and set the dfd flag
+    let page =
+        HieroPage::blank(4, 2, 4).with_conventions(ColorSpace::Srgb, AlphaMode::Premultiplied);
+    let bytes = write_ktx2(&[&[page.clone()][..]], false).unwrap();
+    assert_eq!(bytes[12], VK_FORMAT_R8G8B8A8_SRGB as u8);
+    let dfd_offset = 80 + 24;
+    assert_eq!(bytes[dfd_offset + 12 + 2], 2); // srgb transfer
+    assert_eq!(bytes[dfd_offset + 12 + 3], 1); // premultiplied
+    let linear = page
+        .clone()
+        .with_conventions(ColorSpace::Linear, AlphaMode::Premultiplied);
+    assert!(matches!(
+        write_ktx2(&[&[page][..], &[linear][..]], true),
+        Err(Error::MismatchedTextureLayers)
+    ));
+}
+
+#[test]
+fn dds_layout() {
+    let page = HieroPage::blank(4, 2, 4).with_conventions(ColorSpace::Linear, AlphaMode::Straight);
+    let levels = page.mip_chain();
+    let bytes = write_dds(&[&levels[..]], false).unwrap();
+    assert_eq!(&bytes[..4], b"DDS ");
+    assert_eq!(bytes.len(), 4 + 124 + 32 + 8 + 4);
+
+    // hiero's srgb pages need the dx10 header
+    let page =
+        HieroPage::blank(1, 1, 4).with_conventions(ColorSpace::Srgb, AlphaMode::Premultiplied);
+    let bytes = write_dds(&[&[page][..]], false).unwrap();
+    let dx10 = &bytes[4 + 124..4 + 124 + 20];
+    assert_eq!(dx10[0] as u32, DXGI_FORMAT_R8G8B8A8_UNORM_SRGB);
+    assert_eq!(dx10[16], 2); // premultiplied
+}
+
+#[test]
+fn textures_follow_page_conventions() {
+    let dir = std::env::temp_dir().join(format!("hiero_pack_textures_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let page = HieroPage::blank(2, 1, 4).with_pixels(vec![255, 128, 0, 128, 10, 20, 30, 255]);
+    let mut atlas = HieroAtlas {
+        compressed_pages: vec![CompressedPage::encode(&page, PageEncoding::Raw).unwrap()],
+        ..HieroAtlas::default()
+    };
+    atlas
+        .convert_pages(ColorSpace::Srgb, AlphaMode::Premultiplied)
+        .unwrap();
+    let options = TextureExportOptions {
+        mipmaps: false,
+        ..TextureExportOptions::default()
+    };
+    let texture = atlas.export_textures(&dir, "font", &options).unwrap()[0].clone();
+    assert_eq!(
+        (texture.color_space, texture.alpha_mode),
+        (ColorSpace::Srgb, AlphaMode::Premultiplied)
+    );
+
+    // an explicit alpha mode converts the pages back
+    let options = TextureExportOptions {
+        alpha_mode: Some(AlphaMode::Straight),
+        ..options
+    };
+    let texture = atlas.export_textures(&dir, "font", &options).unwrap()[0].clone();
+    assert_eq!(texture.alpha_mode, AlphaMode::Straight);
+    let bytes = std::fs::read(dir.join(&texture.path)).unwrap();
+    assert_eq!(bytes[bytes.len() - 8..bytes.len() - 4], [255, 128, 0, 128]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,380 @@
+use super::*;
+
+/// How bad a [`Diagnostic`] is
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// the atlas still renders, but probably not the way it was meant to
+    Warning,
+    /// consumers will read out of bounds or render garbage
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// What [`HieroAtlas::validate`] found wrong
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// the header of a page couldn't be read
+    UnreadablePage { page: usize, reason: String },
+    /// `common.pages` doesn't match the number of stored pages
+    PageCountMismatch { declared: i32, actual: usize },
+    /// `common.scale_w`/`common.scale_h` don't match the size of a page
+    PageSizeMismatch {
+        page: usize,
+        declared: (i32, i32),
+        actual: (u32, u32),
+    },
+    /// a glyph points at a page that doesn't exist
+    InvalidPageIndex { glyph: GlyphId, page: i32 },
+    /// a glyph rect has a negative size
+    NegativeSize { glyph: GlyphId, page: usize },
+    /// a glyph rect reaches outside of its page
+    GlyphOutOfBounds {
+        glyph: GlyphId,
+        page: usize,
+        page_size: (u32, u32),
+    },
+    /// two glyph rects on the same page share texels
+    GlyphsOverlap {
+        first: GlyphId,
+        second: GlyphId,
+        page: usize,
+    },
+}
+
+impl DiagnosticKind {
+    /// `(page, glyph)` the diagnostic is about, problems of the whole atlas have neither
+    fn position(&self) -> (Option<i64>, Option<GlyphId>) {
+        match *self {
+            Self::PageCountMismatch { .. } => (None, None),
+            Self::UnreadablePage { page, .. } | Self::PageSizeMismatch { page, .. } => {
+                (Some(page as i64), None)
+            }
+            Self::InvalidPageIndex { glyph, page } => (Some(page as i64), Some(glyph)),
+            Self::NegativeSize { glyph, page } | Self::GlyphOutOfBounds { glyph, page, .. } => {
+                (Some(page as i64), Some(glyph))
+            }
+            Self::GlyphsOverlap { first, page, .. } => (Some(page as i64), Some(first)),
+        }
+    }
+}
+
+/// A single problem found in an atlas
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    fn error(kind: DiagnosticKind) -> Self {
+        Self {
+            severity: Severity::Error,
+            kind,
+        }
+    }
+
+    fn warning(kind: DiagnosticKind) -> Self {
+        Self {
+            severity: Severity::Warning,
+            kind,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        match &self.kind {
+            DiagnosticKind::UnreadablePage { page, reason } => {
+                write!(f, "page {} can't be read: {}", page, reason)
+            }
+            DiagnosticKind::PageCountMismatch { declared, actual } => write!(
+                f,
+                "common declares {} pages but the atlas stores {}",
+                declared, actual
+            ),
+            DiagnosticKind::PageSizeMismatch {
+                page,
+                declared,
+                actual,
+            } => write!(
+                f,
+                "page {} is {}x{} but common declares {}x{}",
+                page, actual.0, actual.1, declared.0, declared.1
+            ),
+            DiagnosticKind::InvalidPageIndex { glyph, page } => write!(
+                f,
+                "glyph {} refers to missing page {}",
+                glyph.describe(),
+                page
+            ),
+            DiagnosticKind::NegativeSize { glyph, .. } => {
+                write!(f, "glyph {} has a negative size", glyph.describe())
+            }
+            DiagnosticKind::GlyphOutOfBounds {
+                glyph,
+                page,
+                page_size,
+            } => write!(
+                f,
+                "glyph {} reaches outside of page {} ({}x{})",
+                glyph.describe(),
+                page,
+                page_size.0,
+                page_size.1
+            ),
+            DiagnosticKind::GlyphsOverlap {
+                first,
+                second,
+                page,
+            } => write!(
+                f,
+                "glyphs {} and {} overlap on page {}",
+                first.describe(),
+                second.describe(),
+                page
+            ),
+        }
+    }
+}
+
+impl HieroAtlas {
+    /// Checks the glyph table against the pages it refers to.\
+    /// Only page headers are decoded. Problems of the whole atlas come first, the rest is ordered by
+    /// page and then by glyph id, overlaps are listed under the lower id of the pair.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let page_count = self.compressed_pages.len();
+        if self.common.pages as i64 != page_count as i64 {
+            diagnostics.push(Diagnostic::error(DiagnosticKind::PageCountMismatch {
+                declared: self.common.pages,
+                actual: page_count,
+            }));
+        }
+
+        let declared = (self.common.scale_w, self.common.scale_h);
+        let sizes: Vec<Option<(u32, u32)>> = self
+            .compressed_pages
+            .iter()
+            .enumerate()
+            .map(|(page, compressed)| match compressed.dimensions() {
+                Ok(actual) => {
+                    if declared.0 as i64 != actual.0 as i64 || declared.1 as i64 != actual.1 as i64
+                    {
+                        diagnostics.push(Diagnostic::error(DiagnosticKind::PageSizeMismatch {
+                            page,
+                            declared,
+                            actual,
+                        }));
+                    }
+                    Some(actual)
+                }
+                Err(err) => {
+                    diagnostics.push(Diagnostic::error(DiagnosticKind::UnreadablePage {
+                        page,
+                        reason: report(&err),
+                    }));
+                    None
+                }
+            })
+            .collect();
+
+        let mut glyphs: Vec<(GlyphId, &HieroBitmapInfo)> = self
+            .bitmap_table
+            .iter()
+            .map(|(&glyph, bitmap)| (glyph, bitmap))
+            .collect();
+        glyphs.sort_by_key(|&(glyph, _)| glyph);
+
+        let mut on_page: Vec<Vec<(GlyphId, &HieroBitmapInfo)>> = vec![Vec::new(); page_count];
+        for &(glyph, bitmap) in glyphs.iter() {
+            if bitmap.page < 0 || bitmap.page as usize >= page_count {
+                diagnostics.push(Diagnostic::error(DiagnosticKind::InvalidPageIndex {
+                    glyph,
+                    page: bitmap.page,
+                }));
+                continue;
+            }
+            let page = bitmap.page as usize;
+            if bitmap.width < 0 || bitmap.height < 0 {
+                diagnostics.push(Diagnostic::error(DiagnosticKind::NegativeSize {
+                    glyph,
+                    page,
+                }));
+                continue;
+            }
+            if let Some(page_size) = sizes[page] {
+                let inside = bitmap.x >= 0
+                    && bitmap.y >= 0
+                    && bitmap.x as i64 + bitmap.width as i64 <= page_size.0 as i64
+                    && bitmap.y as i64 + bitmap.height as i64 <= page_size.1 as i64;
+                if !inside {
+                    diagnostics.push(Diagnostic::error(DiagnosticKind::GlyphOutOfBounds {
+                        glyph,
+                        page,
+                        page_size,
+                    }));
+                }
+            }
+            on_page[page].push((glyph, bitmap));
+        }
+
+        for (page, glyphs) in on_page.iter().enumerate() {
+            for (first, second) in overlapping_glyphs(glyphs) {
+                diagnostics.push(Diagnostic::warning(DiagnosticKind::GlyphsOverlap {
+                    first,
+                    second,
+                    page,
+                }));
+            }
+        }
+
+        // stable, so the diagnostics of a glyph stay in the order they were found
+        diagnostics.sort_by_key(|diagnostic| diagnostic.kind.position());
+        diagnostics
+    }
+
+    /// [`HieroAtlas::validate`] as a `Result`, fails with [`Error::Validation`] holding only the error diagnostics
+    pub fn ensure_valid(&self) -> Result<(), Error> {
+        let errors: Vec<Diagnostic> = self
+            .validate()
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(errors))
+        }
+    }
+}
+
+/// sweeps the rects from left to right and returns every pair that shares texels.\
+/// Empty rects (spaces) hold no texels, glyphs packed into different color channels and glyphs that
+/// reuse the exact same rect (hiero does that for look-alikes like `-` and the soft hyphen) are allowed
+/// to share texels.
+fn overlapping_glyphs(glyphs: &[(GlyphId, &HieroBitmapInfo)]) -> Vec<(GlyphId, GlyphId)> {
+    let mut rects: Vec<(GlyphId, &HieroBitmapInfo)> = glyphs
+        .iter()
+        .copied()
+        .filter(|&(_, bitmap)| bitmap.width > 0 && bitmap.height > 0)
+        .collect();
+    rects.sort_by_key(|&(glyph, bitmap)| (bitmap.x, glyph));
+    let mut pairs = Vec::new();
+    for (index, &(first, a)) in rects.iter().enumerate() {
+        for &(second, b) in rects[index + 1..].iter() {
+            if b.x >= a.x + a.width {
+                break;
+            }
+            let shares_channel = a.channel & b.channel != 0 || a.channel == 0 || b.channel == 0;
+            let same_rect = (a.x, a.y, a.width, a.height) == (b.x, b.y, b.width, b.height);
+            let overlaps = b.y < a.y + a.height && a.y < b.y + b.height;
+            if overlaps && shares_channel && !same_rect {
+                pairs.push((first.min(second), first.max(second)));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[test]
+fn validate_atlas() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let mut atlas = HieroAtlas::new()
+        .with_font_file(font_file)
+        .with_pages(vec![page_file])
+        .build()
+        .unwrap();
+    assert_eq!(atlas.validate(), vec![]);
+    assert!(atlas.ensure_valid().is_ok());
+
+    let glyph = |x, y, page| HieroBitmapInfo {
+        x,
+        y,
+        width: 10,
+        height: 10,
+        page,
+        channel: 15,
+        ..HieroBitmapInfo::default()
+    };
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0xE000), glyph(508, 500, 0));
+    atlas.bitmap_table.insert(GlyphId(0xE001), glyph(0, 0, 3));
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0xE002), glyph(600, 600, 0));
+    atlas
+        .bitmap_table
+        .insert(GlyphId(0xE003), glyph(605, 605, 0));
+    atlas.common.scale_w = 256;
+    // an empty rect inside of another one holds no texels to share
+    let a = *atlas.glyph('A').unwrap();
+    atlas.bitmap_table.insert(
+        GlyphId(0xE004),
+        HieroBitmapInfo {
+            y: a.y + 1,
+            height: 0,
+            ..a
+        },
+    );
+
+    let kinds: Vec<_> = atlas
+        .validate()
+        .into_iter()
+        .map(|diagnostic| diagnostic.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::PageSizeMismatch {
+                page: 0,
+                declared: (256, 512),
+                actual: (512, 512),
+            },
+            DiagnosticKind::GlyphOutOfBounds {
+                glyph: GlyphId(0xE000),
+                page: 0,
+                page_size: (512, 512),
+            },
+            DiagnosticKind::GlyphOutOfBounds {
+                glyph: GlyphId(0xE002),
+                page: 0,
+                page_size: (512, 512),
+            },
+            DiagnosticKind::GlyphsOverlap {
+                first: GlyphId(0xE002),
+                second: GlyphId(0xE003),
+                page: 0,
+            },
+            DiagnosticKind::GlyphOutOfBounds {
+                glyph: GlyphId(0xE003),
+                page: 0,
+                page_size: (512, 512),
+            },
+            DiagnosticKind::InvalidPageIndex {
+                glyph: GlyphId(0xE001),
+                page: 3,
+            },
+        ]
+    );
+    match atlas.ensure_valid() {
+        Err(Error::Validation(errors)) => assert_eq!(errors.len(), 5),
+        _ => panic!("expected validation errors"),
+    }
+}
@@ -0,0 +1,275 @@
+use super::*;
+use std::path::{Path, PathBuf};
+
+/// Knobs for [`pack_fonts`]
+#[derive(Copy, Clone, Debug)]
+pub struct BuildOptions {
+    /// encoding the packed pages are stored with
+    pub page_encoding: PageEncoding,
+    /// also write a rust module per font, see [`HieroAtlas::write_rust_module`]
+    pub rust_module: bool,
+    /// make [`pack_fonts`] fail when any font had a problem, otherwise broken fonts are only warned about
+    pub fail_on_error: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            page_encoding: PageEncoding::Png,
+            rust_module: false,
+            fail_on_error: true,
+        }
+    }
+}
+
+/// A font that was packed into the output directory
+#[derive(Clone, Debug)]
+pub struct PackedFont {
+    /// file stem of the .fnt file, the outputs are named after it
+    pub name: String,
+    pub source: PathBuf,
+    /// the bincode atlas, loadable with [`HieroAtlas::deserialize`]
+    pub atlas_path: PathBuf,
+    pub module_path: Option<PathBuf>,
+}
+
+/// Something that kept a font from being packed, `line` is 1-based
+#[derive(Clone, Debug)]
+pub struct BuildProblem {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for BuildProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// What [`pack_fonts_into`] did
+#[derive(Clone, Debug, Default)]
+pub struct BuildReport {
+    pub fonts: Vec<PackedFont>,
+    /// every file and directory the outputs depend on
+    pub rerun_paths: Vec<PathBuf>,
+    pub problems: Vec<BuildProblem>,
+}
+
+/// Packs every `.fnt` file of `font_dir` (and the pages it names) into `OUT_DIR`, meant to be called from `build.rs`.\
+/// Prints `cargo:rerun-if-changed` for the directory, the fonts and their pages and a `cargo:warning`
+/// per problem. Returns an error naming the broken fonts when [`BuildOptions::fail_on_error`] is set.
+pub fn pack_fonts<P: AsRef<Path>>(
+    font_dir: P,
+    options: &BuildOptions,
+) -> Result<Vec<PackedFont>, Error> {
+    let out_dir = std::env::var_os("OUT_DIR").ok_or(Error::MissingOutDir)?;
+    let report = pack_fonts_into(font_dir, out_dir, options)?;
+    for path in report.rerun_paths.iter() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    for problem in report.problems.iter() {
+        println!("cargo:warning={}", problem);
+    }
+    if options.fail_on_error && !report.problems.is_empty() {
+        return Err(Error::BuildFailed(report.problems));
+    }
+    Ok(report.fonts)
+}
+
+/// Does the work of [`pack_fonts`] without talking to cargo.\
+/// Only io errors on the directories abort, problems with single fonts end up in [`BuildReport::problems`].
+pub fn pack_fonts_into<P: AsRef<Path>, Q: AsRef<Path>>(
+    font_dir: P,
+    out_dir: Q,
+    options: &BuildOptions,
+) -> Result<BuildReport, Error> {
+    let (font_dir, out_dir) = (font_dir.as_ref(), out_dir.as_ref());
+    let mut report = BuildReport::default();
+    report.rerun_paths.push(font_dir.to_path_buf());
+
+    let mut font_paths: Vec<PathBuf> = std::fs::read_dir(font_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    font_paths.retain(|path| path.extension().is_some_and(|ext| ext == "fnt"));
+    font_paths.sort();
+
+    for font_path in font_paths {
+        report.rerun_paths.push(font_path.clone());
+        match pack_font(&font_path, out_dir, options, &mut report.rerun_paths) {
+            Ok(font) => report.fonts.push(font),
+            Err(problems) => report.problems.extend(problems),
+        }
+    }
+    Ok(report)
+}
+
+fn pack_font(
+    font_path: &Path,
+    out_dir: &Path,
+    options: &BuildOptions,
+    rerun_paths: &mut Vec<PathBuf>,
+) -> Result<PackedFont, Vec<BuildProblem>> {
+    let problem = |line: Option<usize>, message: String| BuildProblem {
+        path: font_path.to_path_buf(),
+        line,
+        message,
+    };
+    let name = font_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("font")
+        .to_string();
+    let file =
+        std::fs::File::open(font_path).map_err(|err| vec![problem(None, err.to_string())])?;
+
+    // every problem of the font is collected instead of stopping at the first one
+    let mut problems = Vec::new();
+    let mut records = Vec::new();
+    let mut page_files = Vec::new();
+    let mut reader = FntReader::new(std::io::BufReader::new(file));
+    while let Some(record) = reader.next() {
+        match record {
+            Ok(record) => {
+                if let FntRecord::Page { id, file } = &record {
+                    page_files.push((*id, (reader.line_number(), file.clone())));
+                }
+                records.push(record);
+            }
+            Err(Error::Parse(err)) => problems.push(problem(err.line, err.message)),
+            Err(err) => {
+                problems.push(problem(None, err.report()));
+                break;
+            }
+        }
+    }
+
+    let font_dir = font_path.parent().unwrap_or_else(|| Path::new(""));
+    let mut pages = Vec::new();
+    let page_files = index_pages(page_files).unwrap_or_else(|err| {
+        problems.push(problem(err.line, err.message));
+        Vec::new()
+    });
+    for (line, file) in page_files {
+        let page_path = font_dir.join(&file);
+        rerun_paths.push(page_path.clone());
+        match std::fs::read(&page_path) {
+            Ok(page) => pages.push(page),
+            Err(err) => problems.push(problem(
+                Some(line),
+                format!("can't read page '{}': {}", page_path.display(), err),
+            )),
+        }
+    }
+
+    // icon fonts can name their glyphs in a `{name}.names` file next to the font
+    let names_path = font_path.with_extension("names");
+    let mut glyph_names = GlyphNames::new();
+    if names_path.is_file() {
+        rerun_paths.push(names_path.clone());
+        match std::fs::read_to_string(&names_path)
+            .map_err(Error::from)
+            .and_then(|text| GlyphNames::parse(&text))
+        {
+            Ok(names) => glyph_names = names,
+            Err(Error::Parse(err)) => problems.push(BuildProblem {
+                path: names_path,
+                line: err.line,
+                message: err.message,
+            }),
+            Err(err) => problems.push(problem(None, err.report())),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let mut atlas = HieroAtlas::from_records(records, pages)
+        .map_err(|err| vec![problem(None, err.report())])?;
+    atlas.glyph_names = glyph_names;
+
+    let write = |atlas: &mut HieroAtlas| -> Result<PackedFont, Error> {
+        for page in atlas.compressed_pages.iter_mut() {
+            *page = page.reencode(options.page_encoding)?;
+        }
+        let atlas_path = out_dir.join(format!("{}.bcode", name));
+        std::fs::write(&atlas_path, atlas.to_bytes()?)?;
+        let module_path = if options.rust_module {
+            Some(atlas.write_rust_module(out_dir, &name)?)
+        } else {
+            None
+        };
+        Ok(PackedFont {
+            name: name.clone(),
+            source: font_path.to_path_buf(),
+            atlas_path,
+            module_path,
+        })
+    };
+    write(&mut atlas).map_err(|err| vec![problem(None, err.report())])
+}
+
+#[test]
+fn pack_font_directory() {
+    let out_dir = std::env::temp_dir().join(format!("hiero_pack_build_{}", std::process::id()));
+    let broken_dir = out_dir.join("broken");
+    std::fs::create_dir_all(&broken_dir).unwrap();
+
+    let report = pack_fonts_into("./fonts", &out_dir, &BuildOptions::default()).unwrap();
+    assert!(report.problems.is_empty());
+    assert_eq!(report.fonts.len(), 1);
+    let atlas_bytes = std::fs::read(&report.fonts[0].atlas_path).unwrap();
+    assert_eq!(
+        HieroAtlas::deserialize(atlas_bytes)
+            .unwrap()
+            .compressed_pages
+            .len(),
+        1
+    );
+    assert!(report
+        .rerun_paths
+        .contains(&PathBuf::from("./fonts/uroob.png")));
+
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let broken = font_file
+        .replace("file=\"uroob.png\"", "file=\"missing.png\"")
+        .replace("char id=98 ", "char id=x ");
+    std::fs::write(broken_dir.join("broken.fnt"), broken).unwrap();
+    let broken_header = font_file.replace("lineHeight=", "lineHeight=x");
+    std::fs::write(broken_dir.join("broken_header.fnt"), broken_header).unwrap();
+    std::fs::copy("./fonts/uroob.png", broken_dir.join("uroob.png")).unwrap();
+    let report = pack_fonts_into(&broken_dir, &out_dir, &BuildOptions::default()).unwrap();
+    let problems: Vec<_> = report.problems.iter().map(|p| p.line).collect();
+    assert_eq!(problems, vec![Some(73), Some(3), Some(2)]);
+
+    // glyphs address pages by id, so a font whose page ids skip one can't be packed
+    let gap_dir = out_dir.join("gap");
+    std::fs::create_dir_all(&gap_dir).unwrap();
+    let gap = font_file.replace("page id=0 ", "page id=1 ");
+    std::fs::write(gap_dir.join("gap.fnt"), gap).unwrap();
+    std::fs::copy("./fonts/uroob.png", gap_dir.join("uroob.png")).unwrap();
+    let report = pack_fonts_into(&gap_dir, &out_dir, &BuildOptions::default()).unwrap();
+    assert!(report.fonts.is_empty());
+    assert!(report.problems[0].message.contains("page id 1"));
+
+    // a `.names` sidecar next to the font names its glyphs
+    let named_dir = out_dir.join("named");
+    std::fs::create_dir_all(&named_dir).unwrap();
+    std::fs::write(named_dir.join("uroob.fnt"), &font_file).unwrap();
+    std::fs::copy("./fonts/uroob.png", named_dir.join("uroob.png")).unwrap();
+    std::fs::write(named_dir.join("uroob.names"), "letter_a = U+0061\n").unwrap();
+    let report = pack_fonts_into(&named_dir, &out_dir, &BuildOptions::default()).unwrap();
+    let atlas =
+        HieroAtlas::deserialize(std::fs::read(&report.fonts[0].atlas_path).unwrap()).unwrap();
+    assert_eq!(atlas.glyph_by_name("letter_a"), atlas.glyph('a'));
+    assert!(report.rerun_paths.contains(&named_dir.join("uroob.names")));
+    std::fs::write(named_dir.join("uroob.names"), "letter_a = U+0061\nbroken\n").unwrap();
+    let report = pack_fonts_into(&named_dir, &out_dir, &BuildOptions::default()).unwrap();
+    assert_eq!(report.problems[0].path, named_dir.join("uroob.names"));
+    assert_eq!(report.problems[0].line, Some(2));
+
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
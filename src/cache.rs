@@ -0,0 +1,361 @@
+use super::*;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Hit and miss counts of an [`AtlasCache`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub atlas_hits: u64,
+    pub atlas_misses: u64,
+    pub page_hits: u64,
+    /// every miss is a page decode
+    pub page_misses: u64,
+    /// decoded pages and atlases dropped to stay within the budget
+    pub evictions: u64,
+    /// compressed atlases plus decoded pages currently held
+    pub bytes_used: usize,
+}
+
+impl std::fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "atlases {} hits/{} misses, pages {} hits/{} misses, {} evictions, {} bytes",
+            self.atlas_hits,
+            self.atlas_misses,
+            self.page_hits,
+            self.page_misses,
+            self.evictions,
+            self.bytes_used
+        )
+    }
+}
+
+/// held while an atlas is loaded or a page decoded, callers missing the same thing wait on it
+type Gate = Arc<Mutex<()>>;
+
+struct Entry {
+    atlas: Arc<HieroAtlas>,
+    size: usize,
+    last_used: u64,
+    /// decoded pages with their size and the tick they were last handed out
+    pages: Vec<Option<(Arc<HieroPage>, usize, u64)>>,
+    decoding: Vec<Gate>,
+}
+
+struct State<K> {
+    entries: HashMap<K, Entry>,
+    /// gates of the atlases being loaded right now
+    loading: HashMap<K, Gate>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+/// Atlases shared by key between threads, with pages decoded on first use.\
+/// Atlases and decoded pages are handed out as `Arc`s, so every system asking for the same page gets the
+/// same pixels. Callers missing the same atlas or page at the same time wait for the first one to load or
+/// decode it, so each is loaded and decoded once for as long as it stays cached. Once the cache holds more
+/// than `budget` bytes the least recently used pages are dropped, then the least recently used atlases.
+/// Dropping only releases the cache's reference, callers holding an `Arc` keep their copy.
+pub struct AtlasCache<K = PathBuf> {
+    state: Mutex<State<K>>,
+    budget: usize,
+}
+
+impl<K: Eq + Hash + Clone> AtlasCache<K> {
+    /// a cache that holds at most `budget` bytes of compressed atlases and decoded pages
+    pub fn new(budget: usize) -> Self {
+        Self {
+            state: Mutex::new(State {
+                entries: HashMap::new(),
+                loading: HashMap::new(),
+                tick: 0,
+                stats: CacheStats::default(),
+            }),
+            budget,
+        }
+    }
+
+    /// The atlas cached under `key`, `load` is only called if it isn't cached yet.\
+    /// Other callers asking for `key` while it loads wait for it, if loading fails the next one tries again.
+    pub fn get_or_load<F>(&self, key: K, load: F) -> Result<Arc<HieroAtlas>, Error>
+    where
+        F: FnOnce(&K) -> Result<HieroAtlas, Error>,
+    {
+        let gate = {
+            let mut state = self.lock();
+            if let Some(atlas) = state.atlas_hit(&key) {
+                return Ok(atlas);
+            }
+            state.loading.entry(key.clone()).or_default().clone()
+        };
+        let _loading = wait(&gate);
+        {
+            let mut state = self.lock();
+            if let Some(atlas) = state.atlas_hit(&key) {
+                state.release(&key, &gate);
+                return Ok(atlas);
+            }
+            state.stats.atlas_misses += 1;
+        }
+
+        let loaded = load(&key);
+        let mut state = self.lock();
+        state.release(&key, &gate);
+        let atlas = Arc::new(loaded?);
+        let tick = state.next_tick();
+        let page_count = atlas.compressed_pages.len();
+        let entry = Entry {
+            atlas: atlas.clone(),
+            size: atlas_size(&atlas),
+            last_used: tick,
+            pages: vec![None; page_count],
+            decoding: (0..page_count).map(|_| Gate::default()).collect(),
+        };
+        state.entries.insert(key.clone(), entry);
+        state.update_bytes_used();
+        state.evict(self.budget, &key);
+        Ok(atlas)
+    }
+
+    /// Decoded page `page_index` of the atlas cached under `key`, decoding it if needed.\
+    /// Fails if the atlas isn't cached (anymore) or the page can't be decoded.
+    pub fn page(&self, key: &K, page_index: usize) -> Result<Arc<HieroPage>, Error> {
+        let gate = {
+            let mut state = self.lock();
+            if let Some(page) = state.page_hit(key, page_index)? {
+                return Ok(page);
+            }
+            state.entries[key].decoding[page_index].clone()
+        };
+        let _decoding = wait(&gate);
+        let atlas = {
+            let mut state = self.lock();
+            if let Some(page) = state.page_hit(key, page_index)? {
+                return Ok(page);
+            }
+            state.stats.page_misses += 1;
+            state.entries[key].atlas.clone()
+        };
+
+        let page = Arc::new(atlas.try_unpack_page(page_index)?);
+        let mut state = self.lock();
+        let tick = state.next_tick();
+        // an atlas dropped while decoding still hands out the page, it just isn't cached
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.pages[page_index] = Some((page.clone(), page.pixels().len(), tick));
+            state.update_bytes_used();
+            state.evict(self.budget, key);
+        }
+        Ok(page)
+    }
+
+    /// drops the atlas cached under `key` and its decoded pages
+    pub fn remove(&self, key: &K) -> Option<Arc<HieroAtlas>> {
+        let mut state = self.lock();
+        let entry = state.entries.remove(key);
+        state.update_bytes_used();
+        entry.map(|entry| entry.atlas)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.lock().entries.contains_key(key)
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.update_bytes_used();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    // a panic elsewhere can't leave the state half updated, so a poisoned lock is still usable
+    fn lock(&self) -> MutexGuard<'_, State<K>> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl AtlasCache<PathBuf> {
+    /// the atlas serialized (with bincode) at `path`, read from disk on a miss
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<Arc<HieroAtlas>, Error> {
+        self.get_or_load(path.as_ref().to_path_buf(), |path| {
+            std::fs::read(path)
+                .map_err(Error::from)
+                .and_then(HieroAtlas::deserialize)
+                .map_err(|err| Error::file(path, err))
+        })
+    }
+}
+
+impl<K: Eq + Hash + Clone> State<K> {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn atlas_hit(&mut self, key: &K) -> Option<Arc<HieroAtlas>> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = tick;
+        self.stats.atlas_hits += 1;
+        Some(entry.atlas.clone())
+    }
+
+    /// the page if it's decoded already, fails if the atlas isn't cached or lacks the page
+    fn page_hit(&mut self, key: &K, page_index: usize) -> Result<Option<Arc<HieroPage>>, Error> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key).ok_or(Error::AtlasNotCached)?;
+        entry.last_used = tick;
+        match entry.pages.get_mut(page_index) {
+            Some(Some((page, _, last_used))) => {
+                *last_used = tick;
+                self.stats.page_hits += 1;
+                Ok(Some(page.clone()))
+            }
+            Some(None) => Ok(None),
+            None => Err(Error::PageUnpack(PageUnpackError::InvalidIndex)),
+        }
+    }
+
+    /// forgets the loading gate of `key` once nobody but the caller holds it
+    fn release(&mut self, key: &K, gate: &Gate) {
+        let unused = self
+            .loading
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, gate) && Arc::strong_count(gate) <= 2);
+        if unused {
+            self.loading.remove(key);
+        }
+    }
+
+    fn update_bytes_used(&mut self) {
+        self.stats.bytes_used = self
+            .entries
+            .values()
+            .map(|entry| {
+                let pages: usize = entry.pages.iter().flatten().map(|&(_, size, _)| size).sum();
+                entry.size + pages
+            })
+            .sum();
+    }
+
+    /// drops least recently used pages, then atlases other than `keep`, until `budget` is met
+    fn evict(&mut self, budget: usize, keep: &K) {
+        while self.stats.bytes_used > budget {
+            let oldest_page = self
+                .entries
+                .iter()
+                .flat_map(|(key, entry)| {
+                    entry
+                        .pages
+                        .iter()
+                        .enumerate()
+                        .filter_map(move |(index, page)| Some((page.as_ref()?.2, key, index)))
+                })
+                .min_by_key(|&(last_used, _, _)| last_used)
+                .map(|(_, key, index)| (key.clone(), index));
+            if let Some((key, index)) = oldest_page {
+                if let Some(entry) = self.entries.get_mut(&key) {
+                    entry.pages[index] = None;
+                }
+            } else {
+                let oldest_atlas = self
+                    .entries
+                    .iter()
+                    .filter(|(key, _)| *key != keep)
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                match oldest_atlas {
+                    Some(key) => {
+                        self.entries.remove(&key);
+                    }
+                    // whatever is left is in use right now
+                    None => break,
+                }
+            }
+            self.stats.evictions += 1;
+            self.update_bytes_used();
+        }
+    }
+}
+
+fn wait(gate: &Mutex<()>) -> MutexGuard<'_, ()> {
+    gate.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn atlas_size(atlas: &HieroAtlas) -> usize {
+    atlas
+        .compressed_pages
+        .iter()
+        .chain(atlas.mip_pages.iter().flatten())
+        .map(|page| page.data.len())
+        .sum()
+}
+
+#[test]
+fn shared_cache() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let page_file = std::fs::read("./fonts/uroob.png").unwrap();
+    let atlas = HieroAtlas::new()
+        .with_font_file(font_file)
+        .with_pages(vec![page_file])
+        .build()
+        .unwrap();
+    let loads = std::sync::atomic::AtomicUsize::new(0);
+    let load = |_: &&str| {
+        loads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        // slow enough for the other threads to pile up behind the first load
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        Ok(atlas.clone())
+    };
+
+    // every thread gets the same atlas and the same decoded page, loaded and decoded once
+    let cache = AtlasCache::new(usize::MAX);
+    let pages: Vec<Arc<HieroPage>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    cache.get_or_load("uroob", load).unwrap();
+                    cache.page(&"uroob", 0).unwrap()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+    assert!(pages.iter().all(|page| Arc::ptr_eq(page, &pages[0])));
+    let stats = cache.stats();
+    assert_eq!(loads.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!((stats.atlas_misses, stats.atlas_hits), (1, 3));
+    assert_eq!((stats.page_misses, stats.page_hits), (1, 3));
+    let page_bytes = pages[0].pixels().len();
+    assert_eq!(stats.bytes_used, atlas_size(&atlas) + page_bytes);
+    assert!(matches!(cache.page(&"uroob", 1), Err(Error::PageUnpack(_))));
+    assert!(cache.page(&"other", 0).is_err());
+
+    // a budget that fits the atlases but not a decoded page drops pages first, then old atlases
+    let cache = AtlasCache::new(atlas_size(&atlas) * 2);
+    cache.get_or_load("a", load).unwrap();
+    let page = cache.page(&"a", 0).unwrap();
+    assert_eq!(cache.stats().evictions, 1);
+    assert!(cache.contains(&"a"));
+    cache.page(&"a", 0).unwrap();
+    assert_eq!(cache.stats().page_misses, 2);
+    assert_eq!(page.pixels(), cache.page(&"a", 0).unwrap().pixels());
+    cache.get_or_load("b", load).unwrap();
+    cache.get_or_load("c", load).unwrap();
+    assert!(!cache.contains(&"a") && cache.contains(&"b") && cache.contains(&"c"));
+    assert!(cache.stats().bytes_used <= cache.budget());
+
+    let missing = AtlasCache::new(usize::MAX).load("./fonts/missing.bcode");
+    assert!(matches!(missing, Err(Error::File { .. })));
+}
@@ -0,0 +1,267 @@
+use super::*;
+use std::path::Path;
+
+/// How a corpus file should be read when collecting characters
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CorpusFormat {
+    /// every character counts
+    Text,
+    /// only the contents of string values count (object keys are skipped)
+    Json,
+    /// cells count, delimiters and quoting are skipped and so are the header row and key column
+    /// unless [`CsvOptions`] says otherwise
+    Csv,
+}
+
+impl CorpusFormat {
+    /// guesses the format from a file extension, defaulting to plain text
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let ext = path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("json") => Self::Json,
+            Some("csv") => Self::Csv,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Which parts of a csv corpus are names rather than text, skipped by default like json keys are
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// the first row holds column names
+    pub skip_header: bool,
+    /// the first column holds string keys
+    pub skip_key_column: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            skip_header: true,
+            skip_key_column: true,
+        }
+    }
+}
+
+/// Collects the code points used by a set of localisation/text files
+#[derive(Default)]
+pub struct CorpusScanner {
+    chars: Charset,
+    csv: CsvOptions,
+}
+
+impl CorpusScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv = options;
+        self
+    }
+
+    pub fn scan_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let text = String::from_utf8(std::fs::read(&path)?)?;
+        self.scan_str(&text, CorpusFormat::from_path(&path));
+        Ok(())
+    }
+
+    pub fn scan_str(&mut self, text: &str, format: CorpusFormat) {
+        match format {
+            CorpusFormat::Text => self.collect(text.chars()),
+            CorpusFormat::Json => {
+                for value in json_string_values(text) {
+                    self.collect(value.chars());
+                }
+            }
+            CorpusFormat::Csv => {
+                let skip_rows = self.csv.skip_header as usize;
+                let skip_cells = self.csv.skip_key_column as usize;
+                for row in csv_rows(text).iter().skip(skip_rows) {
+                    for cell in row.iter().skip(skip_cells) {
+                        self.collect(cell.chars());
+                    }
+                }
+            }
+        }
+    }
+
+    /// the characters seen so far
+    pub fn charset(&self) -> &Charset {
+        &self.chars
+    }
+
+    pub fn into_charset(self) -> Charset {
+        self.chars
+    }
+
+    fn collect<I: Iterator<Item = char>>(&mut self, chars: I) {
+        self.chars.extend(chars.filter(|c| !c.is_control()));
+    }
+}
+
+/// The result of checking a charset against the glyphs of an atlas
+pub struct CoverageReport {
+    pub used: usize,
+    pub covered: usize,
+    pub missing: Vec<char>,
+}
+
+impl CoverageReport {
+    pub fn new(used: &Charset, atlas: &HieroAtlas) -> Self {
+        let missing: Vec<char> = used.iter().filter(|&c| atlas.glyph(c).is_none()).collect();
+        Self {
+            used: used.len(),
+            covered: used.len() - missing.len(),
+            missing,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl std::fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = if self.used == 0 {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / self.used as f64
+        };
+        writeln!(
+            f,
+            "{} characters used, {} covered ({:.1}%), {} missing",
+            self.used,
+            self.covered,
+            percent,
+            self.missing.len()
+        )?;
+        for &c in &self.missing {
+            writeln!(f, "  missing U+{:04X} '{}'", c as u32, c)?;
+        }
+        Ok(())
+    }
+}
+
+impl Charset {
+    /// writes the set as a single line of text, the format hiero and [`Charset::from_file`] both accept
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut text: String = self.iter().collect();
+        text.push('\n');
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// returns the decoded string values of a json document, keys are skipped
+fn json_string_values(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('b') | Some('f') => (),
+                    Some('u') => {
+                        let code = json_hex4(&mut chars);
+                        // surrogate pairs are spread across two escapes
+                        let code = match code {
+                            Some(hi @ 0xD800..=0xDBFF) => {
+                                if chars.next() == Some('\\') && chars.next() == Some('u') {
+                                    json_hex4(&mut chars)
+                                        .filter(|lo| (0xDC00..=0xDFFF).contains(lo))
+                                        .map(|lo| 0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00))
+                                } else {
+                                    None
+                                }
+                            }
+                            other => other,
+                        };
+                        value.extend(code.and_then(std::char::from_u32));
+                    }
+                    Some(other) => value.push(other),
+                    None => (),
+                },
+                other => value.push(other),
+            }
+        }
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek() != Some(&':') {
+            values.push(value);
+        }
+    }
+    values
+}
+
+fn json_hex4<I: Iterator<Item = char>>(chars: &mut I) -> Option<u32> {
+    let digits: String = chars.take(4).collect();
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+/// splits csv text into rows of unquoted cells, blank lines are dropped
+fn csv_rows(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\n' | '\r' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            other => cell.push(other),
+        }
+    }
+    row.push(cell);
+    rows.push(row);
+    rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+    rows
+}
+
+#[test]
+fn corpus_formats() {
+    let mut scanner = CorpusScanner::new();
+    scanner.scan_str(
+        r#"{"greeting": "hé", "list": ["😀", "a\"b"]}"#,
+        CorpusFormat::Json,
+    );
+    assert_eq!(scanner.charset().iter().collect::<String>(), "\"abhé😀");
+
+    let csv = "key,text\r\nbye,\"x, \"\"y\"\"\"\n";
+    let mut scanner = CorpusScanner::new().with_csv_options(CsvOptions {
+        skip_header: false,
+        skip_key_column: false,
+    });
+    scanner.scan_str(csv, CorpusFormat::Csv);
+    assert_eq!(scanner.charset().iter().collect::<String>(), " \",bektxy");
+
+    // the same strings give the same charset in either format
+    let mut json = CorpusScanner::new();
+    json.scan_str(r#"{"bye": "x, \"y\"", "hi": "Ünï"}"#, CorpusFormat::Json);
+    let mut csv_scanner = CorpusScanner::new();
+    csv_scanner.scan_str(&format!("{}hi,\"Ünï\"\n", csv), CorpusFormat::Csv);
+    assert_eq!(csv_scanner.charset(), json.charset());
+}
@@ -0,0 +1,500 @@
+use super::*;
+use std::fmt::Write;
+
+const INFO_KEYS: &[&str] = &[
+    "face", "size", "bold", "italic", "charset", "unicode", "stretchH", "smooth", "aa", "padding",
+    "spacing", "outline",
+];
+const COMMON_KEYS: &[&str] = &[
+    "lineHeight",
+    "base",
+    "scaleW",
+    "scaleH",
+    "pages",
+    "packed",
+    "alphaChnl",
+    "redChnl",
+    "greenChnl",
+    "blueChnl",
+];
+const PAGE_KEYS: &[&str] = &["id", "file"];
+const COUNT_KEYS: &[&str] = &["count"];
+const CHAR_KEYS: &[&str] = &[
+    "id", "x", "y", "width", "height", "xoffset", "yoffset", "xadvance", "page", "chnl",
+];
+const KERNING_KEYS: &[&str] = &["first", "second", "amount"];
+
+/// The typed content of one line of a .fnt file, the lines [`FntReader`] reads plus the ones it skips
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DocumentRecord {
+    /// an `info`, `common`, `page`, `char` or `kerning` line
+    Record(FntRecord),
+    Chars {
+        count: u32,
+    },
+    Kernings {
+        count: u32,
+    },
+    /// a line of a kind the model doesn't know (or a blank one), kept as is
+    Unknown(String),
+}
+
+impl DocumentRecord {
+    fn known_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Record(FntRecord::Info(_)) => INFO_KEYS,
+            Self::Record(FntRecord::Common(_)) => COMMON_KEYS,
+            Self::Record(FntRecord::Page { .. }) => PAGE_KEYS,
+            Self::Record(FntRecord::Char { .. }) => CHAR_KEYS,
+            Self::Record(FntRecord::Kerning { .. }) => KERNING_KEYS,
+            Self::Chars { .. } | Self::Kernings { .. } => COUNT_KEYS,
+            Self::Unknown(_) => &[],
+        }
+    }
+}
+
+impl From<FntRecord> for DocumentRecord {
+    fn from(record: FntRecord) -> Self {
+        Self::Record(record)
+    }
+}
+
+/// A line of a [`FntDocument`].\
+/// The text it was read from is written back verbatim until the line is edited through
+/// [`FntLine::record_mut`] or [`FntLine::extra_mut`], edited lines are written in the layout hiero uses.
+#[derive(Clone, Debug)]
+pub struct FntLine {
+    record: DocumentRecord,
+    extra: Vec<(String, String)>,
+    raw: Option<String>,
+    edited: bool,
+    code_page: CodePage,
+}
+
+impl FntLine {
+    pub fn new<R: Into<DocumentRecord>>(record: R) -> Self {
+        Self {
+            record: record.into(),
+            extra: Vec::new(),
+            raw: None,
+            edited: false,
+            code_page: CodePage::Unicode,
+        }
+    }
+
+    /// writes char and kerning ids in `code_page` instead of as code points
+    pub fn with_code_page(mut self, code_page: CodePage) -> Self {
+        self.code_page = code_page;
+        self
+    }
+
+    /// a line of a font whose ids are code points
+    pub fn parse(line: &str) -> Result<Self, String> {
+        Self::parse_with(line, CodePage::Unicode)
+    }
+
+    /// a line of a font whose ids are in `code_page`
+    pub fn parse_with(line: &str, code_page: CodePage) -> Result<Self, String> {
+        let tokens = HieroTokenizer::tokenize_line(line);
+        let record = match FntRecord::from_tokens(&tokens, code_page) {
+            Some(record) => DocumentRecord::Record(record?),
+            None => match tokens.first().and_then(|tok| tok.as_entry()) {
+                Some("chars") => DocumentRecord::Chars {
+                    count: parse::number(&tokens, "count")?,
+                },
+                Some("kernings") => DocumentRecord::Kernings {
+                    count: parse::number(&tokens, "count")?,
+                },
+                _ => DocumentRecord::Unknown(line.to_string()),
+            },
+        };
+        let known_keys = record.known_keys();
+        let extra = match record {
+            DocumentRecord::Unknown(_) => Vec::new(),
+            _ => tokens
+                .iter()
+                .filter_map(|tok| tok.as_pair())
+                .filter(|(key, _)| !known_keys.contains(key))
+                .map(|(key, val)| (key.to_string(), val.to_string()))
+                .collect(),
+        };
+        Ok(Self {
+            record,
+            extra,
+            raw: Some(line.to_string()),
+            edited: false,
+            code_page,
+        })
+    }
+
+    pub fn record(&self) -> &DocumentRecord {
+        &self.record
+    }
+
+    /// marks the line as edited, it is rendered anew when written
+    pub fn record_mut(&mut self) -> &mut DocumentRecord {
+        self.edited = true;
+        &mut self.record
+    }
+
+    /// key/value pairs the record has no field for, in the order they appeared
+    pub fn extra(&self) -> &[(String, String)] {
+        &self.extra
+    }
+
+    /// marks the line as edited, it is rendered anew when written
+    pub fn extra_mut(&mut self) -> &mut Vec<(String, String)> {
+        self.edited = true;
+        &mut self.extra
+    }
+
+    /// true if the line would be written exactly as it was read
+    pub fn is_unchanged(&self) -> bool {
+        self.raw.is_some() && !self.edited
+    }
+
+    /// the line in the layout hiero writes, regardless of how it was read
+    pub fn render(&self) -> String {
+        // glyphs the code page lacks fall back to their id
+        let encode = |glyph: GlyphId| {
+            glyph
+                .as_char()
+                .and_then(|c| self.code_page.encode(c))
+                .unwrap_or(glyph.0)
+        };
+        // writing to a String can't fail
+        let mut text = String::new();
+        let _ = match &self.record {
+            DocumentRecord::Record(FntRecord::Info(info)) => write!(
+                text,
+                "info face=\"{}\" size={} bold={} italic={} charset=\"{}\" unicode={} stretchH={} smooth={} aa={} padding={} spacing={}",
+                info.face,
+                info.size,
+                info.bold,
+                info.italic,
+                info.char_set,
+                info.unicode,
+                info.stretch_h,
+                info.smooth,
+                info.aa,
+                join(&info.padding),
+                join(&info.spacing)
+            )
+            .and_then(|_| optional(&mut text, "outline", info.outline)),
+            DocumentRecord::Record(FntRecord::Common(common)) => write!(
+                text,
+                "common lineHeight={} base={} scaleW={} scaleH={} pages={} packed={}",
+                common.line_height,
+                common.base,
+                common.scale_w,
+                common.scale_h,
+                common.pages,
+                common.packed
+            )
+            .and_then(|_| optional(&mut text, "alphaChnl", common.alpha_chnl))
+            .and_then(|_| optional(&mut text, "redChnl", common.red_chnl))
+            .and_then(|_| optional(&mut text, "greenChnl", common.green_chnl))
+            .and_then(|_| optional(&mut text, "blueChnl", common.blue_chnl)),
+            DocumentRecord::Record(FntRecord::Page { id, file }) => write!(text, "page id={} file=\"{}\"", id, file),
+            DocumentRecord::Chars { count } => write!(text, "chars count={}", count),
+            DocumentRecord::Record(FntRecord::Char { id, bitmap }) => write!(
+                text,
+                "char id={:<8}x={:<5}y={:<5}width={:<5}height={:<5}xoffset={:<5}yoffset={:<5}xadvance={:<5}page={:<5}chnl={}",
+                encode(*id),
+                bitmap.x,
+                bitmap.y,
+                bitmap.width,
+                bitmap.height,
+                bitmap.xoffset,
+                bitmap.yoffset,
+                bitmap.xadvance,
+                bitmap.page,
+                bitmap.channel
+            ),
+            DocumentRecord::Kernings { count } => write!(text, "kernings count={}", count),
+            DocumentRecord::Record(FntRecord::Kerning {
+                first,
+                second,
+                amount,
+            }) => write!(
+                text,
+                "kerning first={} second={} amount={}",
+                encode(*first),
+                encode(*second),
+                amount
+            ),
+            DocumentRecord::Unknown(line) => return line.clone(),
+        };
+        for (key, val) in self.extra.iter() {
+            if val.is_empty() || val.contains(char::is_whitespace) {
+                let _ = write!(text, " {}=\"{}\"", key, val);
+            } else {
+                let _ = write!(text, " {}={}", key, val);
+            }
+        }
+        if let DocumentRecord::Record(FntRecord::Char { .. }) = self.record {
+            text.push(' ');
+        }
+        text
+    }
+}
+
+impl std::fmt::Display for FntLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.raw {
+            Some(raw) if !self.edited => write!(f, "{}", raw),
+            _ => write!(f, "{}", self.render()),
+        }
+    }
+}
+
+/// Every line of a .fnt file in order, including the ones the atlas ignores.\
+/// Writing a document that wasn't edited reproduces the file byte for byte.
+#[derive(Clone, Debug, Default)]
+pub struct FntDocument {
+    pub lines: Vec<FntLine>,
+    crlf: bool,
+    final_newline: bool,
+}
+
+impl FntDocument {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let final_newline = text.ends_with('\n');
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let mut document = Self {
+            lines: Vec::new(),
+            crlf: text.contains("\r\n") || (final_newline && text.ends_with('\r')),
+            final_newline,
+        };
+        let mut code_page = CodePage::Unicode;
+        for (index, line) in text.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let line = FntLine::parse_with(line, code_page)
+                .and_then(|line| {
+                    if let DocumentRecord::Record(FntRecord::Info(info)) = &line.record {
+                        code_page = CodePage::from_info(info).map_err(String::from)?;
+                    }
+                    Ok(line)
+                })
+                .map_err(|err| ParseError::new(Some(index + 1), err))?;
+            document.lines.push(line);
+        }
+        Ok(document)
+    }
+
+    /// the document hiero would write for `atlas`, `page_files` are the file names of its pages
+    pub fn from_atlas(atlas: &HieroAtlas, page_files: &[String]) -> Self {
+        let code_page = CodePage::from_info(&atlas.info).unwrap_or_default();
+        let mut glyphs: Vec<(&GlyphId, &HieroBitmapInfo)> = atlas.bitmap_table.iter().collect();
+        glyphs.sort_by_key(|&(&id, _)| id);
+        let mut kerning: Vec<(&(GlyphId, GlyphId), &i32)> = atlas.kerning_table.iter().collect();
+        kerning.sort();
+
+        let mut records = vec![
+            FntRecord::Info(atlas.info.clone()).into(),
+            FntRecord::Common(atlas.common.clone()).into(),
+        ];
+        records.extend(page_files.iter().enumerate().map(|(id, file)| {
+            FntRecord::Page {
+                id: id as u32,
+                file: file.clone(),
+            }
+            .into()
+        }));
+        records.push(DocumentRecord::Chars {
+            count: glyphs.len() as u32,
+        });
+        records.extend(
+            glyphs
+                .into_iter()
+                .map(|(&id, &bitmap)| FntRecord::Char { id, bitmap }.into()),
+        );
+        records.push(DocumentRecord::Kernings {
+            count: kerning.len() as u32,
+        });
+        records.extend(kerning.into_iter().map(|(&(first, second), &amount)| {
+            FntRecord::Kerning {
+                first,
+                second,
+                amount,
+            }
+            .into()
+        }));
+        Self {
+            lines: records
+                .into_iter()
+                .map(|record| FntLine::new(record).with_code_page(code_page))
+                .collect(),
+            crlf: false,
+            final_newline: true,
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &DocumentRecord> {
+        self.lines.iter().map(|line| &line.record)
+    }
+
+    /// the first line holding a record `select` picks, the line is marked as edited
+    fn find_mut<T, F>(&mut self, mut select: F) -> Option<&mut T>
+    where
+        F: FnMut(&mut DocumentRecord) -> Option<&mut T>,
+    {
+        let index = self
+            .lines
+            .iter_mut()
+            .position(|line| select(&mut line.record).is_some())?;
+        select(self.lines[index].record_mut())
+    }
+
+    /// the `info` record, its line counts as edited from here on
+    pub fn info_mut(&mut self) -> Option<&mut HieroInfo> {
+        self.find_mut(|record| match record {
+            DocumentRecord::Record(FntRecord::Info(info)) => Some(info),
+            _ => None,
+        })
+    }
+
+    /// the `common` record, its line counts as edited from here on
+    pub fn common_mut(&mut self) -> Option<&mut HieroCommon> {
+        self.find_mut(|record| match record {
+            DocumentRecord::Record(FntRecord::Common(common)) => Some(common),
+            _ => None,
+        })
+    }
+
+    /// the `char` record of a glyph, its line counts as edited from here on
+    pub fn glyph_mut<G: Into<GlyphId>>(&mut self, glyph: G) -> Option<&mut HieroBitmapInfo> {
+        let glyph = glyph.into();
+        self.find_mut(|record| match record {
+            DocumentRecord::Record(FntRecord::Char { id, bitmap }) if *id == glyph => Some(bitmap),
+            _ => None,
+        })
+    }
+
+    /// sets the `chars`/`kernings` counts to the number of `char`/`kerning` lines, call after adding or removing any
+    pub fn update_counts(&mut self) {
+        let chars = self
+            .records()
+            .filter(|record| matches!(record, DocumentRecord::Record(FntRecord::Char { .. })))
+            .count() as u32;
+        let kernings = self
+            .records()
+            .filter(|record| matches!(record, DocumentRecord::Record(FntRecord::Kerning { .. })))
+            .count() as u32;
+        for line in self.lines.iter_mut() {
+            let expected = match line.record {
+                DocumentRecord::Chars { count } if count != chars => chars,
+                DocumentRecord::Kernings { count } if count != kernings => kernings,
+                _ => continue,
+            };
+            if let DocumentRecord::Chars { count } | DocumentRecord::Kernings { count } =
+                line.record_mut()
+            {
+                *count = expected;
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for FntDocument {
+    type Err = Error;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl std::fmt::Display for FntDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_ending = if self.crlf { "\r\n" } else { "\n" };
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                f.write_str(line_ending)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        if self.final_newline {
+            f.write_str(line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+/// appends ` key=value` for fields bmfont writes and hiero doesn't
+fn optional(text: &mut String, key: &str, value: Option<i32>) -> std::fmt::Result {
+    match value {
+        Some(value) => write!(text, " {}={}", key, value),
+        None => Ok(()),
+    }
+}
+
+fn join(values: &[i32]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+    values.join(",")
+}
+
+#[test]
+fn lossless_round_trip() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let document = FntDocument::parse(&font_file).unwrap();
+    assert_eq!(document.to_string(), font_file);
+    assert!(document
+        .lines
+        .iter()
+        .all(|line| line.render() == line.raw.clone().unwrap()));
+
+    // hiero's own layout is what from_atlas writes
+    let atlas = HieroAtlas::new().with_font_str(&font_file).build().unwrap();
+    let generated = FntDocument::from_atlas(&atlas, &[String::from("uroob.png")]);
+    assert_eq!(generated.to_string(), font_file);
+
+    // only edited lines are rewritten, unknown lines and keys survive
+    let odd = "info face=\"A\" size=8  bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1 outline=0\r\n\
+        common lineHeight=8 base=6 scaleW=64 scaleH=64 pages=1 packed=0 alphaChnl=1 vendorKey=x\r\n\
+        \r\n\
+        vendor  note=\"keep me\"\r\n\
+        char id=65 x=1 y=2 width=3 height=4 xoffset=0 yoffset=1 xadvance=4 page=0 chnl=15\r\n";
+    let mut document = FntDocument::parse(odd).unwrap();
+    assert_eq!(document.to_string(), odd);
+    assert_eq!(
+        document.lines[1].extra(),
+        &[(String::from("vendorKey"), String::from("x"))]
+    );
+    assert!(matches!(
+        document.lines[1].record(),
+        DocumentRecord::Record(FntRecord::Common(common)) if common.alpha_chnl == Some(1)
+    ));
+    assert!(matches!(
+        document.lines[3].record(),
+        DocumentRecord::Unknown(_)
+    ));
+    // reading doesn't count as an edit
+    assert!(document.lines.iter().all(FntLine::is_unchanged));
+    assert_eq!(document.to_string(), odd);
+
+    document.glyph_mut('A').unwrap().xadvance = 5;
+    document.info_mut().unwrap().size = 9;
+    document.common_mut().unwrap().red_chnl = Some(4);
+    document.update_counts();
+    assert!(document.lines[3].is_unchanged() && !document.lines[4].is_unchanged());
+    let edited = document.to_string();
+    let lines: Vec<&str> = edited.split("\r\n").collect();
+    assert_eq!(lines[0], "info face=\"A\" size=9 bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1 outline=0");
+    assert_eq!(
+        lines[1],
+        "common lineHeight=8 base=6 scaleW=64 scaleH=64 pages=1 packed=0 alphaChnl=1 redChnl=4 vendorKey=x"
+    );
+    assert_eq!(lines[3], "vendor  note=\"keep me\"");
+    assert_eq!(lines[4], "char id=65      x=1    y=2    width=3    height=4    xoffset=0    yoffset=1    xadvance=5    page=0    chnl=15 ");
+
+    // ids of non unicode fonts are decoded with their charset and written back in it
+    let oem = "info face=\"A\" size=8 bold=0 italic=0 charset=\"OEM\" unicode=0 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1\n\
+        char id=219 x=1 y=2 width=3 height=4 xoffset=0 yoffset=1 xadvance=4 page=0 chnl=15\n";
+    let mut document = FntDocument::parse(oem).unwrap();
+    document.glyph_mut('█').unwrap().xadvance = 5;
+    assert!(document.to_string().contains("char id=219 "));
+    let bad_id = oem.replace("id=219", "id=256");
+    assert!(matches!(
+        FntDocument::parse(&bad_id),
+        Err(Error::Parse(ParseError { line: Some(2), .. }))
+    ));
+}
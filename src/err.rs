@@ -1,10 +1,227 @@
+use super::{build_script::BuildProblem, Diagnostic, GlyphId, MetadataFormat, PageEncoding};
+use std::path::PathBuf;
+
+/// Every error of the crate, the variant tells what failed and [`std::error::Error::source`] why
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     ReadWriteError(std::io::Error),
     Utf8ConvertError(std::string::FromUtf8Error),
     DeserializeError(bincode::Error),
-    CustomStatic(&'static str),
-    Custom(String),
+    /// a .bcode file written by a newer version of the format, see [`BCODE_VERSION`](super::BCODE_VERSION)
+    UnsupportedBcodeVersion(u32),
+    PageUnpack(PageUnpackError),
+    PageEncodeError(png::EncodingError),
+    /// the .fnt file is malformed
+    Parse(ParseError),
+    /// the atlas has the error diagnostics listed, see [`HieroAtlas::ensure_valid`](super::HieroAtlas::ensure_valid)
+    Validation(Vec<Diagnostic>),
+    /// json, ron or toml couldn't be written or read
+    Serialization {
+        format: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// an error tied to a file, usually a page the font refers to
+    File {
+        path: PathBuf,
+        source: Box<Error>,
+    },
+    /// a page encoding name that isn't one of [`PageEncoding::ALL`]
+    UnknownPageEncoding(String),
+    /// a metadata format name that isn't one of [`MetadataFormat::ALL`]
+    UnknownMetadataFormat(String),
+    /// a metadata file whose extension isn't json, ron or toml
+    UnknownMetadataExtension(PathBuf),
+    /// the crate was built without the feature this metadata format needs
+    UnsupportedMetadataFormat(MetadataFormat),
+    UnknownTextureContainer(String),
+    UnknownLintRule(String),
+    /// a font `charset` that [`CodePage`](super::CodePage) can't decode
+    UnsupportedCharset(String),
+    /// a charset entry that isn't a code point or a range of them
+    InvalidUnicodeRange(String),
+    /// a png bit depth other than 1, 2, 4, 8 or 16
+    InvalidBitDepth(u8),
+    /// only png can store pages with a palette
+    IndexedPageEncoding(PageEncoding),
+    #[cfg(feature = "qoi")]
+    QoiEncode(qoi::Error),
+    /// the pages of an atlas differ in their number of samples
+    MismatchedPageFormats,
+    /// a glyph is larger than the pages it is repacked into
+    GlyphDoesntFit(GlyphId),
+    /// a glyph refers to a page the atlas doesn't have
+    MissingPage(GlyphId),
+    /// a glyph rect reaches past the edges of its page
+    GlyphOutsidePage(GlyphId),
+    /// there are no pages to export as textures
+    NoPages,
+    /// texture array layers must be rgba pages of the same size and mip count
+    MismatchedTextureLayers,
+    /// [`AtlasCache::page`](crate::cache::AtlasCache::page) was asked for an atlas it doesn't hold
+    AtlasNotCached,
+    /// [`pack_fonts`](crate::build_script::pack_fonts) was called outside of a build script
+    MissingOutDir,
+    /// [`pack_fonts`](crate::build_script::pack_fonts) had problems with the fonts listed
+    BuildFailed(Vec<BuildProblem>),
+    /// a mip level count that isn't a number
+    InvalidMipLevels(String),
+    /// a preview column count that isn't a number
+    InvalidColumnCount(String),
+    /// `coverage --strict` found characters the atlas doesn't have
+    IncompleteCoverage {
+        missing: usize,
+    },
+    /// `check` found error diagnostics
+    CheckFailed {
+        errors: usize,
+    },
+    /// `lint` found error messages
+    LintFailed {
+        errors: usize,
+    },
+    /// neither a .fnt file nor a packed atlas was given
+    MissingInput,
+    /// a .fnt file was given without the pages it refers to
+    MissingPages,
+}
+
+impl Error {
+    pub(crate) fn serialization<E>(format: &'static str, source: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Error::Serialization {
+            format,
+            source: Box::new(source),
+        }
+    }
+
+    pub(crate) fn file<P: Into<PathBuf>, E: Into<Error>>(path: P, source: E) -> Self {
+        Error::File {
+            path: path.into(),
+            source: Box::new(source.into()),
+        }
+    }
+
+    /// the error followed by all of its causes on one line
+    pub fn report(&self) -> String {
+        report(self)
+    }
+}
+
+/// joins `err` and its sources with ": "
+pub fn report(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut text = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ReadWriteError(_) => write!(f, "i/o error"),
+            Error::Utf8ConvertError(_) => write!(f, "text isn't valid utf-8"),
+            Error::DeserializeError(_) => write!(f, "can't (de)serialize atlas"),
+            Error::UnsupportedBcodeVersion(version) => {
+                write!(f, "unsupported .bcode version {}", version)
+            }
+            Error::PageUnpack(_) => write!(f, "can't unpack page"),
+            Error::PageEncodeError(_) => write!(f, "can't encode page as png"),
+            Error::Parse(_) => write!(f, "can't parse font file"),
+            Error::Validation(diagnostics) => {
+                write!(f, "atlas has {} errors", diagnostics.len())?;
+                match diagnostics.first() {
+                    Some(first) => write!(f, ", the first being: {}", first),
+                    None => Ok(()),
+                }
+            }
+            Error::Serialization { format, .. } => write!(f, "can't (de)serialize {}", format),
+            Error::File { path, .. } => write!(f, "'{}'", path.display()),
+            Error::UnknownPageEncoding(name) => write!(f, "unknown page encoding '{}'", name),
+            Error::UnknownMetadataFormat(name) => write!(f, "unknown metadata format '{}'", name),
+            Error::UnknownMetadataExtension(path) => {
+                write!(f, "can't tell the format of '{}'", path.display())
+            }
+            Error::UnsupportedMetadataFormat(format) => write!(
+                f,
+                "'{}' metadata needs the '{}' feature",
+                format.extension(),
+                format.extension()
+            ),
+            Error::UnknownTextureContainer(name) => {
+                write!(f, "unknown texture container '{}'", name)
+            }
+            Error::UnknownLintRule(name) => write!(f, "unknown lint rule '{}'", name),
+            Error::UnsupportedCharset(name) => write!(f, "unsupported charset '{}'", name),
+            Error::InvalidUnicodeRange(entry) => write!(f, "invalid unicode range: '{}'", entry),
+            Error::InvalidBitDepth(depth) => write!(f, "invalid page bit depth {}", depth),
+            Error::IndexedPageEncoding(encoding) => {
+                write!(f, "only png can store indexed pages, not {}", encoding)
+            }
+            #[cfg(feature = "qoi")]
+            Error::QoiEncode(_) => write!(f, "can't encode page as qoi"),
+            Error::MismatchedPageFormats => write!(f, "pages have mismatched pixel formats"),
+            Error::GlyphDoesntFit(id) => write!(f, "glyph {} doesn't fit in a page", id.describe()),
+            Error::MissingPage(id) => {
+                write!(f, "glyph {} references a missing page", id.describe())
+            }
+            Error::GlyphOutsidePage(id) => {
+                write!(f, "glyph {} lies outside of its page", id.describe())
+            }
+            Error::NoPages => write!(f, "no pages to export"),
+            Error::MismatchedTextureLayers => {
+                write!(
+                    f,
+                    "texture array layers must be rgba pages of the same size"
+                )
+            }
+            Error::AtlasNotCached => write!(f, "atlas isn't cached"),
+            Error::MissingOutDir => {
+                write!(f, "OUT_DIR is not set, pack_fonts is meant for build.rs")
+            }
+            Error::BuildFailed(problems) => {
+                write!(f, "failed to pack fonts:")?;
+                problems
+                    .iter()
+                    .try_for_each(|problem| write!(f, "\n{}", problem))
+            }
+            Error::InvalidMipLevels(levels) => write!(f, "invalid mip level count '{}'", levels),
+            Error::InvalidColumnCount(columns) => {
+                write!(f, "'{}' is not a valid column count", columns)
+            }
+            Error::IncompleteCoverage { missing } => {
+                write!(f, "{} characters are missing", missing)
+            }
+            Error::CheckFailed { errors } => write!(f, "{} errors found", errors),
+            Error::LintFailed { errors } => write!(f, "{} lint errors found", errors),
+            Error::MissingInput => write!(f, "either --font or --atlas is required"),
+            Error::MissingPages => write!(f, "--font needs the --pages it refers to"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ReadWriteError(e) => Some(e),
+            Error::Utf8ConvertError(e) => Some(e),
+            Error::DeserializeError(e) => Some(e),
+            Error::PageUnpack(e) => Some(e),
+            Error::PageEncodeError(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Serialization { source, .. } => Some(&**source),
+            Error::File { source, .. } => Some(&**source),
+            #[cfg(feature = "qoi")]
+            Error::QoiEncode(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -19,15 +236,10 @@ impl From<std::string::FromUtf8Error> for Error {
     }
 }
 
+/// the full cause chain, see [`Error::report`]
 impl From<Error> for String {
     fn from(err: Error) -> Self {
-        match err {
-            Error::ReadWriteError(e) => e.to_string(),
-            Error::Utf8ConvertError(e) => e.to_string(),
-            Error::DeserializeError(e) => e.to_string(),
-            Error::Custom(c) => c,
-            Error::CustomStatic(c) => Self::from(c),
-        }
+        err.report()
     }
 }
 
@@ -37,17 +249,107 @@ impl From<bincode::Error> for Error {
     }
 }
 
+impl From<PageUnpackError> for Error {
+    fn from(err: PageUnpackError) -> Self {
+        Error::PageUnpack(err)
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(err: png::EncodingError) -> Self {
+        Error::PageEncodeError(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+/// A malformed .fnt file, `line` is 1-based when known
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<M: Into<String>>(line: Option<usize>, message: M) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a page couldn't be decoded, carried by [`Error::PageUnpack`]
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum PageUnpackError {
     InvalidIndex,
     PageDecodeError(png::DecodingError),
+    UnsupportedEncoding(PageEncoding),
+    CorruptPage(String),
 }
 
 impl std::fmt::Display for PageUnpackError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::InvalidIndex => write!(f, "Invalid Index error"),
-            Self::PageDecodeError(err) => write!(f, "{}", err),
+            Self::PageDecodeError(_) => write!(f, "can't decode png"),
+            Self::UnsupportedEncoding(encoding) => {
+                write!(f, "'{}' pages need the '{}' feature", encoding, encoding)
+            }
+            Self::CorruptPage(reason) => write!(f, "corrupt page: {}", reason),
         }
     }
 }
+
+impl std::error::Error for PageUnpackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::PageDecodeError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn error_chain() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "not found");
+    let err = Error::file("page.png", io);
+    assert_eq!(err.report(), "'page.png': i/o error: not found");
+    assert!(matches!(err, Error::File { .. }));
+
+    let err = Error::from(ParseError::new(Some(2), "lineHeight missing"));
+    assert_eq!(
+        String::from(err),
+        "can't parse font file: line 2: lineHeight missing"
+    );
+
+    // page errors live in the same hierarchy as everything else
+    let atlas = super::HieroAtlas::default();
+    assert!(matches!(
+        atlas.try_unpack_page(0),
+        Err(Error::PageUnpack(PageUnpackError::InvalidIndex))
+    ));
+    let err = "png8".parse::<PageEncoding>().unwrap_err();
+    assert!(matches!(&err, Error::UnknownPageEncoding(name) if name == "png8"));
+    assert_eq!(err.report(), "unknown page encoding 'png8'");
+    assert_eq!(
+        Error::LintFailed { errors: 2 }.report(),
+        "2 lint errors found"
+    );
+}
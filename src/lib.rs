@@ -1,23 +1,19 @@
-use parse::{HieroToken, HieroTokenizer};
+use parse::HieroTokenizer;
+pub use parse::{FntReader, FntRecord};
 use serde::{Deserialize, Serialize};
 use std::collections::*;
 
 mod parse;
 mod err;
 pub mod atlas;
+pub mod build_script;
+pub mod cache;
+pub mod coverage;
+pub mod document;
+pub mod lint;
 
 pub use self::{atlas::*,err::*}; 
 
-pub struct Incomplete<T> {
-    inner: T,
-}
-
-impl<T> Incomplete<T> {
-    pub fn new(inner: T) -> Self {
-        Self { inner }
-    }
-}
-
 #[test]
 fn parse_test() {
     let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
@@ -25,6 +21,6 @@ fn parse_test() {
     let _atlas = HieroAtlas::new()
         .with_pages(vec![page_file])
         .with_font_file(font_file)
-        .expect("file failed to parse")
-        .build();
+        .build()
+        .expect("file failed to parse");
 }
@@ -0,0 +1,300 @@
+use super::*;
+
+/// A check [`LintConfig::lint`] can run, every rule can be turned off or given another severity
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LintRule {
+    /// a visible glyph doesn't move the pen forward
+    ZeroAdvance,
+    /// the font has no glyph for `' '`
+    MissingSpace,
+    /// a glyph hangs below the line box set by `common.line_height`
+    YOffsetOverflow,
+    /// a kerning pair mentions a glyph the font doesn't have
+    KerningMissingGlyph,
+    /// the .fnt file lists the same glyph more than once, only the last one survives parsing.\
+    /// Needs the text of the .fnt file, it is skipped for atlases on their own
+    DuplicateCharId,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 5] = [
+        Self::ZeroAdvance,
+        Self::MissingSpace,
+        Self::YOffsetOverflow,
+        Self::KerningMissingGlyph,
+        Self::DuplicateCharId,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ZeroAdvance => "zero-advance",
+            Self::MissingSpace => "missing-space",
+            Self::YOffsetOverflow => "yoffset-overflow",
+            Self::KerningMissingGlyph => "kerning-missing-glyph",
+            Self::DuplicateCharId => "duplicate-char-id",
+        }
+    }
+
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::ZeroAdvance | Self::MissingSpace | Self::DuplicateCharId => Severity::Error,
+            Self::YOffsetOverflow | Self::KerningMissingGlyph => Severity::Warning,
+        }
+    }
+}
+
+impl std::fmt::Display for LintRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for LintRule {
+    type Err = Error;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.name() == name)
+            .ok_or_else(|| Error::UnknownLintRule(name.to_string()))
+    }
+}
+
+/// A single finding of a [`LintRule`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintMessage {
+    pub rule: LintRule,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl LintMessage {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for LintMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// What [`LintConfig::lint`] found
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LintReport {
+    pub messages: Vec<LintMessage>,
+    /// enabled rules that couldn't run on what they were given
+    pub skipped: Vec<LintRule>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> usize {
+        self.messages.iter().filter(|m| m.is_error()).count()
+    }
+}
+
+/// Which rules run and how bad their findings are, every rule starts out enabled
+/// with its [`LintRule::default_severity`]
+#[derive(Clone, Debug)]
+pub struct LintConfig {
+    severities: BTreeMap<LintRule, Option<Severity>>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            severities: LintRule::ALL
+                .iter()
+                .map(|&rule| (rule, Some(rule.default_severity())))
+                .collect(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// turns a rule off
+    pub fn allow(&mut self, rule: LintRule) -> &mut Self {
+        self.severities.insert(rule, None);
+        self
+    }
+
+    /// turns a rule on with the given severity
+    pub fn set_severity(&mut self, rule: LintRule, severity: Severity) -> &mut Self {
+        self.severities.insert(rule, Some(severity));
+        self
+    }
+
+    /// the severity of a rule, `None` if it is turned off
+    pub fn severity(&self, rule: LintRule) -> Option<Severity> {
+        self.severities.get(&rule).copied().flatten()
+    }
+
+    /// Runs every enabled rule over `atlas`.\
+    /// `source` is the text of the .fnt file the atlas came from, [`LintRule::DuplicateCharId`]
+    /// can only run when it is given since the parsed glyph table has no duplicates left and is
+    /// reported in [`LintReport::skipped`] otherwise.
+    pub fn lint(&self, atlas: &HieroAtlas, source: Option<&str>) -> LintReport {
+        let mut messages = Vec::new();
+        let mut skipped = Vec::new();
+        let mut report = |rule: LintRule, message: String| {
+            if let Some(severity) = self.severity(rule) {
+                messages.push(LintMessage {
+                    rule,
+                    severity,
+                    message,
+                });
+            }
+        };
+
+        let mut glyphs: Vec<(&GlyphId, &HieroBitmapInfo)> = atlas.bitmap_table.iter().collect();
+        glyphs.sort_by_key(|&(&glyph, _)| glyph);
+
+        if atlas.glyph(' ').is_none() {
+            report(
+                LintRule::MissingSpace,
+                String::from("there is no glyph for ' '"),
+            );
+        }
+
+        for (&glyph, bitmap) in glyphs.iter() {
+            if bitmap.xadvance <= 0 && !is_zero_width(glyph) {
+                report(
+                    LintRule::ZeroAdvance,
+                    format!("{} has an xadvance of {}", describe(glyph), bitmap.xadvance),
+                );
+            }
+            if bitmap.height > 0 && bitmap.yoffset + bitmap.height > atlas.common.line_height {
+                report(
+                    LintRule::YOffsetOverflow,
+                    format!(
+                        "{} reaches {} pixels below the line height of {}",
+                        describe(glyph),
+                        bitmap.yoffset + bitmap.height - atlas.common.line_height,
+                        atlas.common.line_height
+                    ),
+                );
+            }
+        }
+
+        let mut kernings: Vec<(&(GlyphId, GlyphId), &i32)> = atlas.kerning_table.iter().collect();
+        kernings.sort();
+        for (&(first, second), _) in kernings {
+            for glyph in [first, second].iter().copied() {
+                if !atlas.bitmap_table.contains_key(&glyph) {
+                    report(
+                        LintRule::KerningMissingGlyph,
+                        format!(
+                            "kerning pair {} {} refers to missing {}",
+                            first.describe(),
+                            second.describe(),
+                            describe(glyph)
+                        ),
+                    );
+                }
+            }
+        }
+
+        match source {
+            Some(source) => {
+                for (glyph, lines) in duplicate_char_ids(source) {
+                    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+                    report(
+                        LintRule::DuplicateCharId,
+                        format!(
+                            "{} is listed on lines {}",
+                            describe(glyph),
+                            lines.join(", ")
+                        ),
+                    );
+                }
+            }
+            None if self.severity(LintRule::DuplicateCharId).is_some() => {
+                skipped.push(LintRule::DuplicateCharId)
+            }
+            None => (),
+        }
+
+        LintReport { messages, skipped }
+    }
+}
+
+/// Returns every glyph with more than one `char` line, along with the (1-based) lines it appears on.\
+/// Ids are decoded with the font's charset first, so `65` and `065` are the same glyph.
+fn duplicate_char_ids(source: &str) -> BTreeMap<GlyphId, Vec<usize>> {
+    let mut ids: BTreeMap<GlyphId, Vec<usize>> = BTreeMap::new();
+    let mut records = FntReader::new(source.as_bytes());
+    while let Some(record) = records.next() {
+        // broken lines are for the parser to report
+        if let Ok(FntRecord::Char { id, .. }) = record {
+            ids.entry(id).or_default().push(records.line_number());
+        }
+    }
+    ids.retain(|_, lines| lines.len() > 1);
+    ids
+}
+
+/// characters that aren't supposed to move the pen, glyphs that aren't characters always are
+fn is_zero_width(glyph: GlyphId) -> bool {
+    glyph.as_char().is_some_and(|c| c.is_control())
+        || matches!(glyph.0, 0x0300..=0x036F | 0x200B..=0x200F | 0x2060..=0x2064 | 0xFEFF)
+}
+
+fn describe(glyph: GlyphId) -> String {
+    format!("glyph {}", glyph.describe())
+}
+
+#[test]
+fn lint_rules() {
+    let font_file = std::fs::read_to_string("./fonts/uroob.fnt").unwrap();
+    let mut atlas = HieroAtlas::new()
+        .with_font_file(font_file.clone())
+        .build()
+        .unwrap();
+    let config = LintConfig::new();
+    assert_eq!(config.lint(&atlas, Some(&font_file)), LintReport::default());
+
+    atlas.bitmap_table.remove(&' '.into());
+    atlas.bitmap_table.get_mut(&'a'.into()).unwrap().xadvance = 0;
+    atlas.bitmap_table.get_mut(&'g'.into()).unwrap().yoffset = 30;
+    atlas
+        .kerning_table
+        .insert(('a'.into(), GlyphId(0xE000)), -1);
+    let duplicated = font_file.replace("char id=98 ", "char id=097 ");
+
+    let report = config.lint(&atlas, Some(&duplicated));
+    let rules: Vec<_> = report
+        .messages
+        .iter()
+        .map(|message| (message.rule, message.severity))
+        .collect();
+    assert_eq!(
+        rules,
+        vec![
+            (LintRule::MissingSpace, Severity::Error),
+            (LintRule::ZeroAdvance, Severity::Error),
+            (LintRule::YOffsetOverflow, Severity::Warning),
+            (LintRule::KerningMissingGlyph, Severity::Warning),
+            (LintRule::DuplicateCharId, Severity::Error),
+        ]
+    );
+    assert!(report.messages[4].message.starts_with("glyph 'a' (U+0061)"));
+    assert_eq!(
+        config.lint(&atlas, None).skipped,
+        vec![LintRule::DuplicateCharId]
+    );
+
+    let mut relaxed = LintConfig::new();
+    relaxed
+        .allow(LintRule::MissingSpace)
+        .set_severity(LintRule::ZeroAdvance, Severity::Warning);
+    relaxed.allow(LintRule::DuplicateCharId);
+    let report = relaxed.lint(&atlas, None);
+    assert_eq!(report.errors(), 0);
+    assert_eq!(report.messages.len(), 3);
+    assert!(report.skipped.is_empty());
+}
@@ -1,13 +1,18 @@
 use hiero_pack::*;
 
 #[cfg(feature = "console")]
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, SubCommand};
 
 fn main() {
     #[cfg(feature = "console")]
     match run_app() {
         Err(err) => {
             eprintln!("Error: {}", err);
+            let mut source = std::error::Error::source(&err);
+            while let Some(cause) = source {
+                eprintln!("  caused by: {}", cause);
+                source = cause.source();
+            }
             std::process::exit(1)
         }
         Ok(_) => std::process::exit(0),
@@ -15,59 +20,394 @@ fn main() {
 }
 
 #[cfg(feature = "console")]
-fn run_app() -> Result<(), String> {
+fn run_app() -> Result<(), Error> {
     let clap_matches = parse_std_in();
+    match clap_matches.subcommand() {
+        ("coverage", Some(sub_matches)) => run_coverage(sub_matches),
+        ("check", Some(sub_matches)) => run_check(sub_matches),
+        ("lint", Some(sub_matches)) => run_lint(sub_matches),
+        ("preview", Some(sub_matches)) => run_preview(sub_matches),
+        ("overlay", Some(sub_matches)) => run_overlay(sub_matches),
+        ("diff", Some(sub_matches)) => run_diff(sub_matches),
+        ("export", Some(sub_matches)) => run_export(sub_matches),
+        ("import", Some(sub_matches)) => run_import(sub_matches),
+        ("codegen", Some(sub_matches)) => run_codegen(sub_matches),
+        _ => run_pack(&clap_matches),
+    }
+}
+
+#[cfg(feature = "console")]
+fn run_pack(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
     let font_path = clap_matches.value_of("font_path").unwrap();
     let page_paths = clap_matches.values_of("page_paths").unwrap();
     let output = clap_matches.value_of("output_opt");
 
-    let font_bytes = std::fs::read(font_path).map_err(Error::from)?;
-    let font_text = String::from_utf8(font_bytes).map_err(Error::from)?;
+    let mut atlas = HieroAtlas::new()
+        .with_page_paths(page_paths)
+        .with_font_path(font_path)
+        .build()?;
 
-    let page_data: Vec<_> = page_paths
-        .map(|page_path| match std::fs::read(page_path) {
-            Ok(data) => data,
-            Err(err) => {
-                eprintln!("Error: {}", err);
-                std::process::exit(1);
-            }
-        })
-        .collect();
+    if let Some(names_path) = clap_matches.value_of("glyph_names_opt") {
+        atlas.glyph_names = GlyphNames::from_file(names_path)?;
+    }
+
+    if let Some(charset_path) = clap_matches.value_of("charset_opt") {
+        let charset = Charset::from_file(charset_path)?;
+        atlas = atlas.subset(&charset)?;
+    }
+
+    if clap_matches.is_present("premultiply_opt") || clap_matches.is_present("linear_opt") {
+        let color_space = if clap_matches.is_present("linear_opt") {
+            ColorSpace::Linear
+        } else {
+            ColorSpace::Srgb
+        };
+        let alpha_mode = if clap_matches.is_present("premultiply_opt") {
+            AlphaMode::Premultiplied
+        } else {
+            AlphaMode::Straight
+        };
+        atlas.convert_pages(color_space, alpha_mode)?;
+    }
+
+    if clap_matches.is_present("optimize_opt") {
+        let options = PngOptimizeOptions {
+            grayscale: clap_matches.is_present("grayscale_opt"),
+            ..PngOptimizeOptions::default()
+        };
+        for savings in atlas.optimize_pages(&options)? {
+            println!("{}", savings);
+        }
+    }
+
+    if let Some(encoding) = clap_matches.value_of("encoding_opt") {
+        atlas.reencode_pages(encoding.parse()?)?;
+    }
 
-    let atlas = HieroAtlas::new()
-        .with_pages(page_data)
-        .with_font_file(font_text)?
-        .build();
+    if clap_matches.is_present("mipmaps_opt") {
+        let max_levels = match clap_matches.value_of("mip_levels_opt") {
+            Some(levels) => Some(
+                levels
+                    .parse()
+                    .map_err(|_| Error::InvalidMipLevels(levels.to_string()))?,
+            ),
+            None => None,
+        };
+        atlas.generate_mips(&GlyphMipOptions { max_levels })?;
+        let safe_levels = atlas.glyph_safe_mip_levels();
+        let levels = atlas
+            .mip_pages
+            .iter()
+            .map(|mips| mips.len() as u32 + 1)
+            .max();
+        if max_levels.is_none() && levels.is_some_and(|levels| levels > safe_levels) {
+            eprintln!(
+                "warning: glyphs share texels after {} mip levels, the padding/spacing gutter is too narrow for more (--mip-levels caps the chain)",
+                safe_levels
+            );
+        }
+    }
+
+    if let Some(container) = clap_matches.value_of("texture_opt") {
+        let options = TextureExportOptions {
+            container: container.parse()?,
+            array: clap_matches.is_present("texture_array_opt"),
+            mipmaps: !clap_matches.is_present("no_mipmaps_opt"),
+            color_space: None,
+            alpha_mode: None,
+        };
+        let output_path = std::path::Path::new(output.unwrap_or("atlas.bcode"));
+        let texture_dir = output_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let name = output_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("atlas");
+        for texture in atlas.export_textures(texture_dir, name, &options)? {
+            println!("wrote {}", texture.path);
+        }
+    }
 
     if clap_matches.is_present("dump_opt") {
         atlas
             .bitmap_table
             .iter()
-            .filter(|(key, _)| !key.is_whitespace())
+            .filter(|(key, _)| !key.as_char().is_some_and(char::is_whitespace))
             .for_each(|(key, val)| {
-                println!("key:'{}',val:{}", key, val);
+                println!("key:{},val:{}", key.describe(), val);
             });
     }
 
     //serialize Atlus to a binary format
-    let atlas_bytes = bincode::serialize(&atlas).unwrap();
+    let atlas_bytes = atlas.to_bytes()?;
 
     //attempt write binary blob to disk
     let write_result = match output {
         Some(path) => std::fs::write(path, atlas_bytes),
         None => std::fs::write("atlas.bcode", atlas_bytes),
     };
-    write_result.map_err(Error::from)?;
+    write_result?;
+
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_coverage(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let mut scanner = coverage::CorpusScanner::new();
+    for corpus_path in clap_matches.values_of("corpus_paths").unwrap() {
+        scanner.scan_file(corpus_path)?;
+    }
+
+    if let Some(charset_path) = clap_matches.value_of("emit_charset_opt") {
+        scanner.charset().write_file(charset_path)?;
+    }
+
+    match load_atlas(clap_matches)? {
+        Some(atlas) => {
+            let report = coverage::CoverageReport::new(scanner.charset(), &atlas);
+            print!("{}", report);
+            if !report.is_complete() && clap_matches.is_present("strict_opt") {
+                return Err(Error::IncompleteCoverage {
+                    missing: report.missing.len(),
+                });
+            }
+        }
+        None => println!("{} characters used", scanner.charset().len()),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_check(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = load_atlas_with_pages(clap_matches)?;
+    let diagnostics = atlas.validate();
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    println!("{} errors, {} warnings", errors, diagnostics.len() - errors);
+    if errors > 0 {
+        return Err(Error::CheckFailed { errors });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_lint(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let mut config = lint::LintConfig::new();
+    let overrides = [
+        ("allow_opt", None),
+        ("warn_opt", Some(Severity::Warning)),
+        ("deny_opt", Some(Severity::Error)),
+    ];
+    for &(arg, severity) in overrides.iter() {
+        for name in clap_matches.values_of(arg).into_iter().flatten() {
+            let rule: lint::LintRule = name.parse()?;
+            match severity {
+                Some(severity) => config.set_severity(rule, severity),
+                None => config.allow(rule),
+            };
+        }
+    }
+
+    let atlas = load_atlas(clap_matches)?.ok_or(Error::MissingInput)?;
+    let source = match clap_matches.value_of("font_path") {
+        Some(font_path) => Some(std::fs::read_to_string(font_path)?),
+        None => None,
+    };
+    let report = config.lint(&atlas, source.as_deref());
+    for message in report.messages.iter() {
+        println!("{}", message);
+    }
+    for rule in report.skipped.iter() {
+        println!("skipped[{}]: needs the .fnt file, pass --font", rule);
+    }
+    let errors = report.errors();
+    println!(
+        "{} errors, {} warnings",
+        errors,
+        report.messages.len() - errors
+    );
+    if errors > 0 {
+        return Err(Error::LintFailed { errors });
+    }
+    Ok(())
+}
 
+#[cfg(feature = "console")]
+fn run_preview(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = load_atlas_with_pages(clap_matches)?;
+    let mut options = PreviewOptions::default();
+    if let Some(columns) = clap_matches.value_of("columns_opt") {
+        options.columns = columns
+            .parse()
+            .map_err(|_| Error::InvalidColumnCount(columns.to_string()))?;
+    }
+    if let Some(text) = clap_matches.value_of("text_opt") {
+        options.sample_text = text.replace("\\n", "\n");
+    }
+    options.boxes = !clap_matches.is_present("no_boxes_opt");
+
+    let sheet = atlas.render_preview(&options)?;
+    let output = clap_matches.value_of("output_opt").unwrap_or("preview.png");
+    std::fs::write(output, sheet.encode_png()?)?;
+    println!("wrote {}", output);
     Ok(())
 }
 
+#[cfg(feature = "console")]
+fn run_overlay(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = load_atlas_with_pages(clap_matches)?;
+    let options = OverlayOptions {
+        annotate: clap_matches.is_present("annotate_opt"),
+    };
+    let output_dir = std::path::Path::new(clap_matches.value_of("output_dir_opt").unwrap_or("."));
+    let name = clap_matches.value_of("name_opt").unwrap_or("overlay");
+    let overlays = atlas.render_overlays(&options)?;
+    for (page_index, overlay) in overlays.iter().enumerate() {
+        let path = output_dir.join(format!("{}_page{}.png", name, page_index));
+        std::fs::write(&path, overlay.encode_png()?)?;
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_diff(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let load = |path_arg: &str, pages_arg: &str| -> Result<HieroAtlas, Error> {
+        let path = clap_matches.value_of(path_arg).unwrap();
+        let bytes = std::fs::read(path)?;
+        if !path.ends_with(".fnt") {
+            return HieroAtlas::deserialize(bytes);
+        }
+        HieroAtlas::new()
+            .with_font_reader(&bytes[..])
+            .with_page_paths(clap_matches.values_of(pages_arg).into_iter().flatten())
+            .build()
+    };
+    let before = load("before_path", "before_pages_opt")?;
+    let after = load("after_path", "after_pages_opt")?;
+
+    let diff = AtlasDiff::new(&before, &after)?;
+    if clap_matches.is_present("json_opt") {
+        let json = serde_json::to_string_pretty(&diff).map_err(|err| Error::Serialization {
+            format: "json",
+            source: Box::new(err),
+        })?;
+        println!("{}", json);
+    } else {
+        print!("{}", diff);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_export(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = load_atlas_with_pages(clap_matches)?;
+    let output = std::path::Path::new(clap_matches.value_of("output_opt").unwrap());
+    let format = match clap_matches.value_of("format_opt") {
+        Some(format) => format.parse()?,
+        None => MetadataFormat::from_path(output)
+            .ok_or_else(|| Error::UnknownMetadataExtension(output.to_path_buf()))?,
+    };
+    let dir = output
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let name = output
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("atlas");
+    let path = atlas.export_metadata(dir, name, format)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_import(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = HieroAtlas::import_metadata(clap_matches.value_of("metadata_path").unwrap())?;
+    let output = clap_matches.value_of("output_opt").unwrap_or("atlas.bcode");
+    let atlas_bytes = atlas.to_bytes()?;
+    std::fs::write(output, atlas_bytes)?;
+    println!("wrote {}", output);
+    Ok(())
+}
+
+#[cfg(feature = "console")]
+fn run_codegen(clap_matches: &clap::ArgMatches) -> Result<(), Error> {
+    let atlas = load_atlas_with_pages(clap_matches)?;
+    let output_dir = clap_matches.value_of("output_dir_opt").unwrap_or(".");
+    let name = clap_matches.value_of("name_opt").unwrap_or("font");
+    let path = atlas.write_rust_module(output_dir, name)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+/// like [`load_atlas`], but a .fnt file is only accepted along with its pages
+#[cfg(feature = "console")]
+fn load_atlas_with_pages(clap_matches: &clap::ArgMatches) -> Result<HieroAtlas, Error> {
+    if clap_matches.is_present("font_path") && !clap_matches.is_present("page_paths") {
+        return Err(Error::MissingPages);
+    }
+    load_atlas(clap_matches)?.ok_or(Error::MissingInput)
+}
+
+/// loads the atlas named by a subcommand's `--font` (.fnt) or `--atlas` (packed) argument
+#[cfg(feature = "console")]
+fn load_atlas(clap_matches: &clap::ArgMatches) -> Result<Option<HieroAtlas>, Error> {
+    if let Some(font_path) = clap_matches.value_of("font_path") {
+        let atlas = HieroAtlas::new()
+            .with_font_path(font_path)
+            .with_page_paths(clap_matches.values_of("page_paths").into_iter().flatten())
+            .build()?;
+        Ok(Some(atlas))
+    } else if let Some(atlas_path) = clap_matches.value_of("atlas_path") {
+        let atlas_bytes = std::fs::read(atlas_path)?;
+        Ok(Some(HieroAtlas::deserialize(atlas_bytes)?))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "console")]
+fn atlas_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("font_path")
+            .short("f")
+            .long("font")
+            .help("the .fnt file to check against")
+            .takes_value(true),
+        Arg::with_name("page_paths")
+            .short("p")
+            .long("pages")
+            .help("the page files of the .fnt file")
+            .requires("font_path")
+            .multiple(true)
+            .takes_value(true),
+        Arg::with_name("atlas_path")
+            .short("a")
+            .long("atlas")
+            .help("the packed atlas to check against")
+            .conflicts_with("font_path")
+            .takes_value(true),
+    ]
+}
+
 #[cfg(feature = "console")]
 fn parse_std_in<'a>() -> clap::ArgMatches<'a> {
+    let lint_rules: Vec<&str> = lint::LintRule::ALL.iter().map(|rule| rule.name()).collect();
+    let page_encodings: Vec<&str> = PageEncoding::ALL
+        .iter()
+        .filter(|encoding| encoding.is_supported())
+        .map(|encoding| encoding.name())
+        .collect();
     App::new("hiero_pack")
         .version("0.1.0")
         .about("packs the files generated from the hiero font tool")
         .author("Khadeem D.")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name("font_path")
                 .short("f")
@@ -92,11 +432,280 @@ fn parse_std_in<'a>() -> clap::ArgMatches<'a> {
                 .help("the name of the output file")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("charset_opt")
+                .long("charset-file")
+                .help("only keep glyphs found in this file (plain text or unicode ranges like U+0020-U+007E)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("glyph_names_opt")
+                .long("glyph-names")
+                .help("a sidecar file naming glyphs, one 'name = id' per line (ids like U+E001, 0xE001 or 57345)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("premultiply_opt")
+                .long("premultiply")
+                .help("stores pages with premultiplied alpha"),
+        )
+        .arg(
+            Arg::with_name("linear_opt")
+                .long("linear")
+                .help("stores pages in linear space instead of srgb"),
+        )
+        .arg(
+            Arg::with_name("optimize_opt")
+                .long("optimize")
+                .help("re-encodes the pages as the smallest lossless png"),
+        )
+        .arg(
+            Arg::with_name("grayscale_opt")
+                .long("grayscale")
+                .requires("optimize_opt")
+                .help("lets --optimize store gray pages with 1-2 samples per pixel instead of 3-4"),
+        )
+        .arg(
+            Arg::with_name("encoding_opt")
+                .short("e")
+                .long("page-encoding")
+                .help("how pages are stored in the atlas")
+                .possible_values(&page_encodings)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("mipmaps_opt")
+                .long("mipmaps")
+                .help("stores glyph aware mip levels of every page in the atlas"),
+        )
+        .arg(
+            Arg::with_name("mip_levels_opt")
+                .long("mip-levels")
+                .requires("mipmaps_opt")
+                .help("number of mip levels (base included), defaults to the full chain down to 1x1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("texture_opt")
+                .long("texture")
+                .help("also exports the pages as gpu textures next to the output file")
+                .possible_values(&["ktx2", "dds"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("texture_array_opt")
+                .long("texture-array")
+                .requires("texture_opt")
+                .help("exports all pages as layers of a single texture array"),
+        )
+        .arg(
+            Arg::with_name("no_mipmaps_opt")
+                .long("no-mipmaps")
+                .requires("texture_opt")
+                .help("only exports the base level of each texture"),
+        )
         .arg(
             Arg::with_name("dump_opt")
                 .short("d")
                 .long("dump")
                 .help("dumps the char info to stdout(for debugging purposes)"),
         )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("checks the glyph rects and page sizes of a font for consistency")
+                .args(&atlas_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("reports common hiero export mistakes, exits with an error if any rule at error level fires")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("allow_opt")
+                        .long("allow")
+                        .help("turns a rule off")
+                        .possible_values(&lint_rules)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("warn_opt")
+                        .long("warn")
+                        .help("reports a rule as a warning")
+                        .possible_values(&lint_rules)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("deny_opt")
+                        .long("deny")
+                        .help("reports a rule as an error")
+                        .possible_values(&lint_rules)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("preview")
+                .about("renders a contact sheet of every glyph to a png")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("output_opt")
+                        .short("o")
+                        .long("output")
+                        .help("the png to write, defaults to preview.png")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("columns_opt")
+                        .long("columns")
+                        .help("glyph cells per row")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("text_opt")
+                        .long("text")
+                        .help("the sample paragraph set below the grid, \\n starts a new line")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("no_boxes_opt")
+                        .long("no-boxes")
+                        .help("leaves out glyph rects, advance boxes and baselines"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("overlay")
+                .about("draws every glyph rect onto a copy of its page, flagging out of bounds and overlapping rects")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("output_dir_opt")
+                        .long("output-dir")
+                        .help("where the overlay pngs are written, defaults to the working directory")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("name_opt")
+                        .long("name")
+                        .help("overlays are written as <name>_page<index>.png, defaults to 'overlay'")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("annotate_opt")
+                        .long("annotate")
+                        .help("labels every rect with its character or code point"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("lists the glyphs, metrics, kerning pairs and header fields that differ between two fonts")
+                .arg(
+                    Arg::with_name("before_path")
+                        .help("the old font, either a .fnt file or a packed atlas")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("after_path")
+                        .help("the new font, either a .fnt file or a packed atlas")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("before_pages_opt")
+                        .long("before-pages")
+                        .help("page files of the old .fnt, glyph bitmaps are only compared when both sides have pages")
+                        .multiple(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("after_pages_opt")
+                        .long("after-pages")
+                        .help("page files of the new .fnt")
+                        .multiple(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("json_opt")
+                        .long("json")
+                        .help("prints the differences as json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("writes the font metadata as json, ron or toml with the pages as sibling pngs")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("output_opt")
+                        .short("o")
+                        .long("output")
+                        .help("the metadata file to write, pages are written next to it")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format_opt")
+                        .long("format")
+                        .help("overrides the format implied by the output extension")
+                        .possible_values(&["json", "ron", "toml"])
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("packs metadata written by 'export' back into a binary atlas")
+                .arg(
+                    Arg::with_name("metadata_path")
+                        .help("the .json, .ron or .toml file to read")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output_opt")
+                        .short("o")
+                        .long("output")
+                        .help("the binary atlas to write, defaults to atlas.bcode")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("codegen")
+                .about("writes a rust module that embeds the font, with its pages next to it")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("output_dir_opt")
+                        .long("output-dir")
+                        .help("where the module and its pages are written, defaults to the working directory")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("name_opt")
+                        .long("name")
+                        .help("the module is written as <name>.rs, defaults to 'font'")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("coverage")
+                .about("collects the characters used by text/json/csv files and reports the ones missing from a font")
+                .args(&atlas_args())
+                .arg(
+                    Arg::with_name("emit_charset_opt")
+                        .short("e")
+                        .long("emit-charset")
+                        .help("writes the collected characters to a charset file")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("strict_opt")
+                        .long("strict")
+                        .help("exit with an error when characters are missing"),
+                )
+                .arg(
+                    Arg::with_name("corpus_paths")
+                        .help("the text, json or csv files to scan")
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
         .get_matches()
 }
@@ -1,7 +1,10 @@
 use super::*;
 
+mod reader;
 mod tests;
 
+pub use self::reader::*;
+
 #[derive(Clone, Copy, Debug)]
 pub struct InSlice {
     lbound: usize,
@@ -143,210 +146,124 @@ impl HieroTokenizer {
     }
 }
 
-pub fn parse_common(table: &[Vec<HieroToken>]) -> Result<HieroCommon, &'static str> {
-    let mut common = HieroCommon::default();
-    let common_line = &table[1];
-
+pub fn parse_common(common_line: &[HieroToken]) -> Result<HieroCommon, String> {
     if common_line.len() < 7 {
-        return Err("'common' line lacks columns");
+        return Err(String::from("'common' line lacks columns"));
     }
-
-    common.line_height = find_pair_by_key(common_line, "lineHeight")
-        .ok_or("lineHeight missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant parse lineHeight")?;
-
-    common.base = find_pair_by_key(common_line, "base")
-        .ok_or("base missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant parse: base")?;
-
-    common.scale_w = find_pair_by_key(common_line, "scaleW")
-        .ok_or("scaleW missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant parse: scaleW")?;
-
-    common.scale_h = find_pair_by_key(common_line, "scaleH")
-        .ok_or("scaleH missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant parse: scaleH")?;
-
-    common.pages = find_pair_by_key(common_line, "pages")
-        .ok_or("pages missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant parse: pages")?;
-
-    common.packed = find_pair_by_key(common_line, "packed")
-        .ok_or("packed missing")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("cant packed lineHeight")?;
-
-    Ok(common)
+    Ok(HieroCommon {
+        line_height: number(common_line, "lineHeight")?,
+        base: number(common_line, "base")?,
+        scale_w: number(common_line, "scaleW")?,
+        scale_h: number(common_line, "scaleH")?,
+        pages: number(common_line, "pages")?,
+        packed: number(common_line, "packed")?,
+        alpha_chnl: optional_number(common_line, "alphaChnl")?,
+        red_chnl: optional_number(common_line, "redChnl")?,
+        green_chnl: optional_number(common_line, "greenChnl")?,
+        blue_chnl: optional_number(common_line, "blueChnl")?,
+    })
 }
 
-pub fn parse_info(table: &[Vec<HieroToken>]) -> Result<HieroInfo, &'static str> {
-    let mut info = HieroInfo::default();
-
-    let info_line = &table[0];
+pub fn parse_info(info_line: &[HieroToken]) -> Result<HieroInfo, String> {
     if info_line.len() < 12 {
-        return Err("'info' line doesn't have right number of columns");
+        return Err(String::from(
+            "'info' line doesn't have right number of columns",
+        ));
     }
+    Ok(HieroInfo {
+        face: value(info_line, "face")?.into(),
+        size: number(info_line, "size")?,
+        bold: number(info_line, "bold")?,
+        italic: number(info_line, "italic")?,
+        char_set: value(info_line, "charset")?.into(),
+        unicode: number(info_line, "unicode")?,
+        stretch_h: number(info_line, "stretchH")?,
+        smooth: number(info_line, "smooth")?,
+        aa: number(info_line, "aa")?,
+        padding: numbers(value(info_line, "padding")?),
+        spacing: numbers(value(info_line, "spacing")?),
+        outline: optional_number(info_line, "outline")?,
+    })
+}
 
-    info_line
-        .iter()
-        .find_map(|tok| tok.as_entry())
-        .ok_or("info line not found")?;
-
-    info.face = find_pair_by_key(info_line, "face")
-        .ok_or("face not found")?
-        .1
-        .into();
-
-    info.size = find_pair_by_key(info_line, "size")
-        .ok_or("size not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("size failed to parse")?;
-
-    info.bold = find_pair_by_key(info_line, "bold")
-        .ok_or("bold not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("bold bailed to parse")?;
-
-    info.italic = find_pair_by_key(info_line, "italic")
-        .ok_or("italic not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("italic parse failed")?;
-
-    info.face = find_pair_by_key(info_line, "face")
-        .ok_or("face not found")?
-        .1
-        .into();
-
-    info.char_set = find_pair_by_key(info_line, "charset")
-        .ok_or("charset not found")?
-        .1
-        .into();
-
-    info.unicode = find_pair_by_key(info_line, "unicode")
-        .ok_or("charset not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("unicode failed to parse")?;
-
-    info.stretch_h = find_pair_by_key(info_line, "stretchH")
-        .ok_or("charset not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("unicode failed to parse")?;
-
-    info.smooth = find_pair_by_key(info_line, "smooth")
-        .ok_or("smooth not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("smooth failed to parse")?;
+pub fn parse_char(
+    char_line: &[HieroToken],
+    code_page: CodePage,
+) -> Result<(GlyphId, HieroBitmapInfo), String> {
+    let field = |key| number(char_line, key);
+    let id = char_id(field("id")?, code_page)?;
+    let bitmap = HieroBitmapInfo {
+        x: field("x")?,
+        y: field("y")?,
+        width: field("width")?,
+        height: field("height")?,
+        xoffset: field("xoffset")?,
+        yoffset: field("yoffset")?,
+        xadvance: field("xadvance")?,
+        page: field("page")?,
+        channel: field("chnl")?,
+    };
+    Ok((id, bitmap))
+}
 
-    info.aa = find_pair_by_key(info_line, "aa")
-        .ok_or("aa not found")?
-        .1
-        .parse()
-        .ok()
-        .ok_or("aa failed to parse")?;
+pub fn parse_kerning(
+    kerning_line: &[HieroToken],
+    code_page: CodePage,
+) -> Result<((GlyphId, GlyphId), i32), String> {
+    let first = char_id(number(kerning_line, "first")?, code_page)?;
+    let second = char_id(number(kerning_line, "second")?, code_page)?;
+    Ok(((first, second), number(kerning_line, "amount")?))
+}
 
-    info.padding = find_pair_by_key(info_line, "padding")
-        .ok_or("padding not found")?
-        .1
-        .split(',')
-        .filter_map(|num| num.parse().ok())
-        .collect();
+pub fn parse_page(page_line: &[HieroToken]) -> Result<(u32, String), String> {
+    let id = number(page_line, "id")?;
+    let file = value(page_line, "file")?.to_string();
+    Ok((id, file))
+}
 
-    info.spacing = find_pair_by_key(info_line, "spacing")
-        .ok_or("spacing not found")?
-        .1
-        .split(',')
-        .filter_map(|num| num.parse().ok())
-        .collect();
-    Ok(info)
+/// the value of `key`, the error names the key if it's absent
+pub(crate) fn value<'a>(line: &'a [HieroToken], key: &str) -> Result<&'a str, String> {
+    find_pair_by_key(line, key)
+        .map(|(_, val)| val)
+        .ok_or_else(|| format!("'{}' missing", key))
 }
 
-pub fn parse_glyphs(table: &[Vec<HieroToken>]) -> Result<HashMap<char, HieroBitmapInfo>, &'static str> {
-    let mut glyph_table: HashMap<char, HieroBitmapInfo> = HashMap::new();
+/// the value of `key` as a number, the error names the key and the value it couldn't parse
+pub(crate) fn number<T: std::str::FromStr>(line: &[HieroToken], key: &str) -> Result<T, String> {
+    let val = value(line, key)?;
+    val.parse()
+        .map_err(|_| format!("'{}' isn't a number: '{}'", key, val))
+}
 
-    let line_iter = table
-        .iter()
-        .filter(|table| table[0].as_entry().is_some() && table[0].as_entry().unwrap() == "char");
+/// a comma separated list like `padding=1,1,1,1`, entries that aren't numbers are skipped
+fn numbers(list: &str) -> Vec<i32> {
+    list.split(',').filter_map(|num| num.parse().ok()).collect()
+}
 
-    for char_line in line_iter {
-        let char_data: Vec<i32> = char_line[1..]
-            .iter()
-            .filter_map(|tok| tok.as_pair())
-            .filter_map(|(_key, val)| val.parse().ok())
-            .collect();
-        let id: char = std::char::from_u32(char_data[0] as u32).unwrap();
-        glyph_table.insert(
-            id,
-            HieroBitmapInfo {
-                x: char_data[1],
-                y: char_data[2],
-                width: char_data[3],
-                height: char_data[4],
-                xoffset: char_data[5],
-                yoffset: char_data[6],
-                xadvance: char_data[7],
-                page: char_data[8],
-                channel: char_data[9],
-            },
-        );
+/// `None` if `key` is absent, an error if it's there but not a number
+fn optional_number(line: &[HieroToken], key: &str) -> Result<Option<i32>, String> {
+    match find_pair_by_key(line, key) {
+        Some(_) => number(line, key).map(Some),
+        None => Ok(None),
     }
-
-    Ok(glyph_table)
 }
 
-pub fn parse_kerning_table(
-    table: &[Vec<HieroToken>],
-) -> Result<HashMap<(char, char), i32>, &'static str> {
-    let mut kerning_table = HashMap::new();
-
-    let line_iter = table
-        .iter()
-        .filter(|table| table[0].as_entry().is_some() && table[0].as_entry().unwrap() == "kerning");
-
-    for char_line in line_iter {
-        let kerning_data: Vec<i32> = char_line[1..]
-            .iter()
-            .flat_map(|tok| tok.as_pair())
-            .filter_map(|(_, val)| val.parse().ok())
-            .collect();
-        let first = std::char::from_u32(kerning_data[0] as u32).unwrap();
-        let second = std::char::from_u32(kerning_data[0] as u32).unwrap();
-        let amount = kerning_data[0];
-        kerning_table.insert((first, second), amount);
+/// ids of unicode fonts are kept as they are, even if they aren't characters (icon fonts using glyph indices),
+/// other charsets are decoded to unicode
+fn char_id(id: i32, code_page: CodePage) -> Result<GlyphId, String> {
+    if id < 0 {
+        return Err(format!("id {} is negative", id));
+    }
+    match code_page {
+        CodePage::Unicode | CodePage::Symbol => Ok(GlyphId(id as u32)),
+        _ => code_page
+            .decode(id as u32)
+            .map(GlyphId::from)
+            .ok_or_else(|| format!("id {} isn't a character in the font's charset", id)),
     }
-
-    Ok(kerning_table)
 }
 
-fn find_pair_by_key<'a>(line: &'a [HieroToken], key: &'a str) -> Option<(&'a str, &'a str)> {
+fn find_pair_by_key<'a>(line: &'a [HieroToken], key: &str) -> Option<(&'a str, &'a str)> {
     line.iter()
         .filter_map(|tok| tok.as_pair())
         .find(|(k, _v)| k == &key)
@@ -0,0 +1,210 @@
+use super::*;
+use std::io::BufRead;
+
+/// A line of a .fnt file the atlas cares about
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FntRecord {
+    Info(HieroInfo),
+    Common(HieroCommon),
+    Page {
+        id: u32,
+        file: String,
+    },
+    Char {
+        id: GlyphId,
+        bitmap: HieroBitmapInfo,
+    },
+    Kerning {
+        first: GlyphId,
+        second: GlyphId,
+        amount: i32,
+    },
+}
+
+impl FntRecord {
+    /// the record a tokenized line holds, `None` if the line isn't one the atlas cares about
+    pub(crate) fn from_tokens(
+        tokens: &[HieroToken],
+        code_page: CodePage,
+    ) -> Option<Result<Self, String>> {
+        let record = match tokens.first().and_then(|tok| tok.as_entry())? {
+            "info" => parse_info(tokens).map(FntRecord::Info),
+            "common" => parse_common(tokens).map(FntRecord::Common),
+            "page" => parse_page(tokens).map(|(id, file)| FntRecord::Page { id, file }),
+            "char" => {
+                parse_char(tokens, code_page).map(|(id, bitmap)| FntRecord::Char { id, bitmap })
+            }
+            "kerning" => parse_kerning(tokens, code_page).map(|((first, second), amount)| {
+                FntRecord::Kerning {
+                    first,
+                    second,
+                    amount,
+                }
+            }),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+/// Parses a .fnt file one line at a time, only the current line is held in memory.\
+/// `chars`/`kernings` count lines and lines it doesn't know are skipped. A malformed line yields an
+/// [`Error::Parse`] carrying its line number, iterating further picks up at the next line.\
+/// Char and kerning ids are decoded with the [`CodePage`] of the last `info` line, if its charset isn't
+/// supported every `char` and `kerning` line after it is an error too.
+pub struct FntReader<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    code_page: CodePage,
+    /// the charset of the last `info` line if it isn't supported
+    unsupported_charset: Option<String>,
+}
+
+impl<R: BufRead> FntReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            code_page: CodePage::Unicode,
+            unsupported_charset: None,
+        }
+    }
+
+    /// 1-based line of the record (or error) returned last
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// the code page ids are currently decoded with
+    pub fn code_page(&self) -> CodePage {
+        self.code_page
+    }
+}
+
+impl<R: BufRead> Iterator for FntReader<R> {
+    type Item = Result<FntRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(err) => return Some(Err(err.into())),
+            }
+            let tokens = HieroTokenizer::tokenize_line(&self.line);
+            let record = match FntRecord::from_tokens(&tokens, self.code_page) {
+                Some(Ok(FntRecord::Info(info))) => match CodePage::from_info(&info) {
+                    Ok(code_page) => {
+                        self.code_page = code_page;
+                        self.unsupported_charset = None;
+                        Ok(FntRecord::Info(info))
+                    }
+                    Err(err) => {
+                        self.unsupported_charset = Some(info.char_set);
+                        Err(err.to_string())
+                    }
+                },
+                Some(Ok(FntRecord::Char { .. })) | Some(Ok(FntRecord::Kerning { .. }))
+                    if self.unsupported_charset.is_some() =>
+                {
+                    Err(format!(
+                        "can't decode ids of the unsupported charset '{}'",
+                        self.unsupported_charset.as_deref().unwrap_or_default()
+                    ))
+                }
+                Some(record) => record,
+                None => continue,
+            };
+            let line_number = self.line_number;
+            return Some(record.map_err(|err| ParseError::new(Some(line_number), err).into()));
+        }
+    }
+}
+
+#[test]
+fn read_records() {
+    let text = "info face=\"Tiny\" size=8 bold=0 italic=0 charset=\"\" unicode=0 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1\n\
+        common lineHeight=8 base=6 scaleW=64 scaleH=64 pages=1 packed=0\n\
+        page id=0 file=\"tiny.png\"\n\
+        chars count=2\n\
+        char id=65 x=1 y=2 width=3 height=4 xoffset=0 yoffset=1 xadvance=4 page=0 chnl=15\n\
+        char id=-1 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=0 page=0 chnl=15\n\
+        kernings count=1\n\
+        kerning first=65 second=86 amount=-2\n";
+    let mut reader = FntReader::new(text.as_bytes());
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.by_ref() {
+        match record {
+            Ok(record) => records.push(record),
+            Err(Error::Parse(err)) => errors.push(err.line),
+            Err(err) => panic!("{}", err),
+        }
+    }
+    assert_eq!(errors, vec![Some(6)]);
+    assert_eq!(reader.line_number(), 8);
+    assert_eq!(records.len(), 5);
+    assert!(matches!(&records[2], FntRecord::Page { id: 0, file } if file == "tiny.png"));
+    assert!(
+        matches!(&records[3], FntRecord::Char { id: GlyphId(65), bitmap } if bitmap.channel == 15)
+    );
+    assert!(matches!(
+        records[4],
+        FntRecord::Kerning {
+            first: GlyphId(65),
+            second: GlyphId(86),
+            amount: -2
+        }
+    ));
+}
+
+#[test]
+fn decode_charset_ids() {
+    let font = |charset: &str, id: u32| {
+        format!(
+            "info face=\"Tiny\" size=8 bold=0 italic=0 charset=\"{}\" unicode=0 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1\n\
+            char id={} x=1 y=2 width=3 height=4 xoffset=0 yoffset=1 xadvance=4 page=0 chnl=15\n",
+            charset, id
+        )
+    };
+    let ids = |text: &str| {
+        FntReader::new(text.as_bytes())
+            .map(|record| match record {
+                Ok(FntRecord::Char { id, .. }) => Ok(id.as_char()),
+                Ok(_) => Ok(None),
+                Err(Error::Parse(err)) => Err(err.line),
+                Err(err) => panic!("{}", err),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&font("", 0x41)), vec![Ok(None), Ok(Some('A'))]);
+    assert_eq!(ids(&font("OEM", 0xDB)), vec![Ok(None), Ok(Some('█'))]);
+    // an id that isn't a character in the charset is an error, not a panic
+    assert_eq!(ids(&font("OEM", 0x100)), vec![Ok(None), Err(Some(2))]);
+    // unicode fonts keep ids that aren't characters, icon fonts use them as glyph indices
+    let glyph_index = font("", 0xD800);
+    let mut records = FntReader::new(glyph_index.as_bytes()).skip(1);
+    assert!(matches!(
+        records.next(),
+        Some(Ok(FntRecord::Char {
+            id: GlyphId(0xD800),
+            ..
+        }))
+    ));
+    // ids of a charset that isn't supported can't be decoded at all
+    assert_eq!(
+        ids(&font("KLINGON", 0x41)),
+        vec![Err(Some(1)), Err(Some(2))]
+    );
+    #[cfg(feature = "charsets")]
+    {
+        assert_eq!(
+            ids(&font("SHIFTJIS", 0x82A0)),
+            vec![Ok(None), Ok(Some('あ'))]
+        );
+        assert_eq!(ids(&font("ANSI", 0x80)), vec![Ok(None), Ok(Some('€'))]);
+    }
+}
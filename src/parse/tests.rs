@@ -80,4 +80,41 @@ fn harder() {
             },
         ]
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn errors_name_their_key() {
+    let info = "info face=\"A\" size=8 bold=0 italic=0 charset=\"\" unicode=0 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=1,1";
+    let common = "common lineHeight=8 base=6 scaleW=64 scaleH=64 pages=1 packed=0";
+    let info_error = |from: &str, to: &str| {
+        parse_info(&HieroTokenizer::tokenize_line(&info.replace(from, to))).unwrap_err()
+    };
+    let common_error = |from: &str, to: &str| {
+        parse_common(&HieroTokenizer::tokenize_line(&common.replace(from, to))).unwrap_err()
+    };
+    assert_eq!(
+        info_error("unicode=0", "unicode=x"),
+        "'unicode' isn't a number: 'x'"
+    );
+    assert_eq!(info_error("unicode=0", "unikode=0"), "'unicode' missing");
+    assert_eq!(
+        info_error("stretchH=100", "stretchH=y"),
+        "'stretchH' isn't a number: 'y'"
+    );
+    assert_eq!(
+        common_error("packed=0", "packed=z"),
+        "'packed' isn't a number: 'z'"
+    );
+
+    let char_line =
+        "char id=65 x=1 y=2 width=3 height=4 xoffset=0 yoffset=1 xadvance=4 page=0 chnl=15";
+    let char_error = |from: &str, to: &str| {
+        let line = char_line.replace(from, to);
+        parse_char(&HieroTokenizer::tokenize_line(&line), CodePage::Unicode).unwrap_err()
+    };
+    assert_eq!(
+        char_error("xadvance=4", "xadvance=?"),
+        "'xadvance' isn't a number: '?'"
+    );
+    assert_eq!(char_error("chnl=15", ""), "'chnl' missing");
+}